@@ -0,0 +1,63 @@
+//! Aspect lock: a locked window keeps its image's shape while the user resizes it. The
+//! width the user dragged to is kept and the height follows.
+
+/// Height may differ from the locked shape by this much (logical px) before it is fixed,
+/// so rounding doesn't start a resize loop.
+const TOLERANCE: f64 = 0.5;
+
+/// Width over height of a `width`×`height` image, or `None` for an empty one.
+pub fn ratio(size: (f64, f64)) -> Option<f64> {
+    let (w, h) = size;
+    let r = w / h;
+    (w > 0.0 && h > 0.0 && r.is_finite()).then_some(r)
+}
+
+/// Logical size a window resized to `size` should take to keep `ratio`, or `None` when
+/// it already does or `ratio` isn't usable.
+pub fn constrain(size: (f64, f64), ratio: f64) -> Option<(f64, f64)> {
+    if !ratio.is_finite() || ratio <= 0.0 {
+        return None;
+    }
+    let (w, h) = size;
+    let target_h = (w / ratio).round().max(1.0);
+    ((target_h - h).abs() > TOLERANCE).then_some((w, target_h))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ratio_of_an_image() {
+        assert_eq!(ratio((1600.0, 900.0)), Some(16.0 / 9.0));
+        assert_eq!(ratio((0.0, 900.0)), None);
+        assert_eq!(ratio((1600.0, 0.0)), None);
+        assert_eq!(ratio((f64::NAN, 1.0)), None);
+    }
+
+    #[test]
+    fn height_follows_width() {
+        assert_eq!(constrain((800.0, 800.0), 2.0), Some((800.0, 400.0)));
+        assert_eq!(constrain((300.0, 100.0), 0.5), Some((300.0, 600.0)));
+    }
+
+    #[test]
+    fn matching_sizes_are_left_alone() {
+        assert_eq!(constrain((800.0, 400.0), 2.0), None);
+        // 1000 / (16/9) = 562.5, which rounds to 563.
+        assert_eq!(constrain((1000.0, 563.0), 16.0 / 9.0), None);
+    }
+
+    #[test]
+    fn tiny_windows_keep_a_pixel() {
+        assert_eq!(constrain((1.0, 50.0), 100.0), Some((1.0, 1.0)));
+    }
+
+    #[test]
+    fn unusable_ratios_are_ignored() {
+        assert_eq!(constrain((800.0, 600.0), 0.0), None);
+        assert_eq!(constrain((800.0, 600.0), -1.0), None);
+        assert_eq!(constrain((800.0, 600.0), f64::INFINITY), None);
+        assert_eq!(constrain((800.0, 600.0), f64::NAN), None);
+    }
+}
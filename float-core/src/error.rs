@@ -0,0 +1,25 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("{}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("serde: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("settings: {0}")]
+    SettingsShape(String),
+    #[error("settings version {0} is newer than this build")]
+    SettingsTooNew(u32),
+}
+
+impl Error {
+    pub fn io(path: &Path, source: io::Error) -> Error {
+        Error::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+}
@@ -0,0 +1,269 @@
+//! Window sizes for Fit to Image. Every mode is limited to the monitor's work area so a
+//! large image never leaves the window partly off screen.
+
+use serde::{Deserialize, Serialize};
+
+/// Smallest window edge a fit aims for, in logical px. A long thin image keeps its shape
+/// instead, so one edge can end up shorter to stay inside the work area.
+const MIN_SIZE: f64 = 50.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "snake_case")]
+pub enum FitMode {
+    /// Keep the larger window edge and shrink the other to the image's aspect ratio.
+    #[default]
+    Aspect,
+    /// One image pixel per physical screen pixel.
+    ActualPixels,
+    /// One image pixel per logical point, so images look the same size on any display.
+    ActualSize,
+    /// As large as the monitor's work area allows.
+    Screen,
+}
+
+impl FitMode {
+    pub const ALL: [FitMode; 4] = [
+        FitMode::Aspect,
+        FitMode::ActualPixels,
+        FitMode::ActualSize,
+        FitMode::Screen,
+    ];
+
+    pub fn menu_id(self) -> &'static str {
+        match self {
+            FitMode::Aspect => "fit_mode_aspect",
+            FitMode::ActualPixels => "fit_mode_actual_pixels",
+            FitMode::ActualSize => "fit_mode_actual_size",
+            FitMode::Screen => "fit_mode_screen",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FitMode::Aspect => "Match Aspect Ratio",
+            FitMode::ActualPixels => "Actual Pixels",
+            FitMode::ActualSize => "Actual Size",
+            FitMode::Screen => "Fit to Screen",
+        }
+    }
+}
+
+/// Logical window size for an image of `image` pixels.
+///
+/// `current` is the window's logical size, `scale` its scale factor and `bounds` the
+/// logical size of its monitor's work area, when known.
+pub fn target_size(
+    mode: FitMode,
+    image: (f64, f64),
+    current: (f64, f64),
+    scale: f64,
+    bounds: Option<(f64, f64)>,
+) -> (f64, f64) {
+    let (img_w, img_h) = image;
+    let scale = if scale > 0.0 { scale } else { 1.0 };
+    let size = match (mode, bounds) {
+        (FitMode::Aspect, _) | (FitMode::Screen, None) => aspect_fit(current, img_w / img_h),
+        (FitMode::ActualPixels, _) => (img_w / scale, img_h / scale),
+        (FitMode::ActualSize, _) => (img_w, img_h),
+        (FitMode::Screen, Some((max_w, max_h))) => {
+            let s = (max_w / img_w).min(max_h / img_h);
+            (img_w * s, img_h * s)
+        }
+    };
+    let size = grow_to_minimum(size);
+    match bounds {
+        Some(bounds) => shrink_into(size, bounds),
+        None => size,
+    }
+}
+
+/// Anchors on the larger current dimension and reduces the other to match `aspect`,
+/// never growing either.
+fn aspect_fit(current: (f64, f64), aspect: f64) -> (f64, f64) {
+    let (cur_w, cur_h) = current;
+    if cur_w >= cur_h {
+        let mut target_w = cur_w;
+        let mut target_h = target_w / aspect;
+        if target_h > cur_h && target_h > 0.0 {
+            let scale = cur_h / target_h;
+            target_w *= scale;
+            target_h = cur_h;
+        }
+        (target_w, target_h)
+    } else {
+        let mut target_h = cur_h;
+        let mut target_w = target_h * aspect;
+        if target_w > cur_w && target_w > 0.0 {
+            let scale = cur_w / target_w;
+            target_h *= scale;
+            target_w = cur_w;
+        }
+        (target_w, target_h)
+    }
+}
+
+/// Scales `size` up, keeping its aspect ratio, until both edges are at least
+/// [`MIN_SIZE`].
+fn grow_to_minimum(size: (f64, f64)) -> (f64, f64) {
+    let (w, h) = size;
+    let s = (MIN_SIZE / w).max(MIN_SIZE / h);
+    if s.is_finite() && s > 1.0 {
+        (w * s, h * s)
+    } else {
+        size
+    }
+}
+
+/// Scales `size` down, keeping its aspect ratio, until it fits in `bounds`.
+fn shrink_into(size: (f64, f64), bounds: (f64, f64)) -> (f64, f64) {
+    let (w, h) = size;
+    let (max_w, max_h) = bounds;
+    if max_w <= 0.0 || max_h <= 0.0 {
+        return size;
+    }
+    let s = (max_w / w).min(max_h / h).min(1.0);
+    (w * s, h * s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: Option<(f64, f64)> = Some((1440.0, 875.0));
+
+    fn assert_close(actual: (f64, f64), expected: (f64, f64)) {
+        let close = (actual.0 - expected.0).abs() < 1e-9 && (actual.1 - expected.1).abs() < 1e-9;
+        assert!(close, "{actual:?} != {expected:?}");
+    }
+
+    #[test]
+    fn aspect_keeps_the_larger_edge() {
+        // Landscape window, portrait image: the height is kept, the width shrinks.
+        let size = target_size(FitMode::Aspect, (600.0, 900.0), (800.0, 600.0), 1.0, SCREEN);
+        assert_eq!(size, (400.0, 600.0));
+        // Portrait window, landscape image: the width is kept, the height shrinks.
+        let size = target_size(
+            FitMode::Aspect,
+            (1600.0, 900.0),
+            (400.0, 700.0),
+            2.0,
+            SCREEN,
+        );
+        assert_close(size, (400.0, 225.0));
+    }
+
+    #[test]
+    fn aspect_never_grows_the_window() {
+        let (w, h) = target_size(FitMode::Aspect, (2000.0, 1000.0), (800.0, 600.0), 1.0, None);
+        assert!(w <= 800.0 && h <= 600.0);
+        assert_eq!((w, h), (800.0, 400.0));
+    }
+
+    #[test]
+    fn actual_pixels_follow_the_scale_factor() {
+        let size = target_size(
+            FitMode::ActualPixels,
+            (1200.0, 800.0),
+            (400.0, 400.0),
+            2.0,
+            SCREEN,
+        );
+        assert_eq!(size, (600.0, 400.0));
+        let size = target_size(
+            FitMode::ActualPixels,
+            (1200.0, 800.0),
+            (400.0, 400.0),
+            1.0,
+            SCREEN,
+        );
+        assert_eq!(size, (1200.0, 800.0));
+    }
+
+    #[test]
+    fn actual_size_ignores_the_scale_factor() {
+        let size = target_size(
+            FitMode::ActualSize,
+            (640.0, 480.0),
+            (400.0, 400.0),
+            2.0,
+            SCREEN,
+        );
+        assert_eq!(size, (640.0, 480.0));
+    }
+
+    #[test]
+    fn screen_fills_the_work_area() {
+        let size = target_size(FitMode::Screen, (800.0, 400.0), (400.0, 400.0), 1.0, SCREEN);
+        assert_eq!(size, (1440.0, 720.0));
+        let size = target_size(FitMode::Screen, (400.0, 800.0), (400.0, 400.0), 1.0, SCREEN);
+        assert_eq!(size, (437.5, 875.0));
+    }
+
+    #[test]
+    fn screen_without_bounds_falls_back_to_aspect() {
+        let screen = target_size(FitMode::Screen, (800.0, 400.0), (600.0, 600.0), 1.0, None);
+        let aspect = target_size(FitMode::Aspect, (800.0, 400.0), (600.0, 600.0), 1.0, None);
+        assert_eq!(screen, aspect);
+    }
+
+    #[test]
+    fn large_images_shrink_into_the_work_area() {
+        let (w, h) = target_size(
+            FitMode::ActualSize,
+            (4000.0, 3000.0),
+            (400.0, 400.0),
+            1.0,
+            SCREEN,
+        );
+        assert!(w <= 1440.0 && h <= 875.0);
+        assert!((w / h - 4.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn small_images_grow_to_the_minimum_keeping_their_shape() {
+        let size = target_size(
+            FitMode::ActualSize,
+            (10.0, 4.0),
+            (400.0, 400.0),
+            1.0,
+            SCREEN,
+        );
+        assert_close(size, (125.0, MIN_SIZE));
+    }
+
+    #[test]
+    fn thin_strips_keep_their_shape_inside_the_work_area() {
+        // Growing to 50 px tall would make it 7500 px wide, so the work area wins.
+        let (w, h) = target_size(
+            FitMode::ActualPixels,
+            (3000.0, 20.0),
+            (400.0, 400.0),
+            1.0,
+            SCREEN,
+        );
+        assert!(w <= 1440.0 && h <= 875.0);
+        assert!((w / h - 150.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn unusable_scale_and_bounds_are_ignored() {
+        let size = target_size(
+            FitMode::ActualPixels,
+            (300.0, 200.0),
+            (400.0, 400.0),
+            0.0,
+            Some((0.0, 0.0)),
+        );
+        assert_eq!(size, (300.0, 200.0));
+    }
+
+    #[test]
+    fn menu_ids_are_distinct() {
+        let mut ids: Vec<_> = FitMode::ALL.iter().map(|m| m.menu_id()).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), FitMode::ALL.len());
+        assert!(ids.iter().all(|id| id.starts_with("fit_mode_")));
+    }
+}
@@ -0,0 +1,17 @@
+//! Float's platform-independent logic, shared by the Tauri shell (`src-tauri`) and the
+//! legacy winit app (`src/main.rs`): the settings file and its migrations, selections,
+//! and the window sizes for fit and aspect lock. Nothing here talks to a window system,
+//! so `cargo test` runs it on any machine, with or without a display.
+
+pub mod aspect;
+mod error;
+pub mod fit;
+pub mod migrate;
+pub mod selection;
+pub mod settings;
+pub mod store;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+pub mod transform;
+
+pub use error::Error;
@@ -0,0 +1,183 @@
+//! Versioned `settings.json`. Every file Float has ever written is read into the current
+//! [`PersistedState`], one typed step per schema version:
+//!
+//! - version 0: the winit build (`src/main.rs`) before it shared this crate, recognised
+//!   by its `fit_window` flag.
+//! - version 1: the Tauri shell before the `version` field. Fields were only ever added
+//!   with defaults, so it parses as the current shape.
+//! - version 2: the current shape, with `version`.
+//!
+//! When a field is renamed or changes type, freeze today's struct here as the input of a
+//! new step, bump [`CURRENT_VERSION`] and add the step to [`upgrade`].
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    settings::{PersistedState, WindowSizeUnits},
+    Error,
+};
+
+pub const CURRENT_VERSION: u32 = 2;
+
+/// Settings written by the winit build.
+#[derive(Deserialize)]
+struct WinitState {
+    #[serde(default)]
+    fit_window: bool,
+    #[serde(default)]
+    aspect_lock: bool,
+    last_file: Option<String>,
+    window_w: Option<f64>, // logical inner size
+    window_h: Option<f64>,
+}
+
+impl From<WinitState> for PersistedState {
+    fn from(old: WinitState) -> Self {
+        PersistedState {
+            last_file: old.last_file,
+            aspect_lock: old.aspect_lock,
+            window_w: old.window_w,
+            window_h: old.window_h,
+            window_size_units: Some(WindowSizeUnits::Logical),
+            fit_on_open: old.fit_window,
+            ..Default::default()
+        }
+    }
+}
+
+fn version_of(value: &Value) -> Result<u32, Error> {
+    if !value.is_object() {
+        return Err(Error::SettingsShape("not a JSON object".into()));
+    }
+    match value.get("version") {
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| Error::SettingsShape(format!("invalid version {v}"))),
+        None if value.get("fit_window").is_some() => Ok(0),
+        None => Ok(1),
+    }
+}
+
+/// Reads settings of any known version.
+pub fn upgrade(bytes: &[u8]) -> Result<PersistedState, Error> {
+    let value: Value = serde_json::from_slice(bytes)?;
+    let version = version_of(&value)?;
+    if version < CURRENT_VERSION {
+        log::info!(target: "settings", "upgrading from={version} to={CURRENT_VERSION}");
+    }
+    let mut st = match version {
+        0 => PersistedState::from(serde_json::from_value::<WinitState>(value)?),
+        1 | CURRENT_VERSION => serde_json::from_value::<PersistedState>(value)?,
+        version => return Err(Error::SettingsTooNew(version)),
+    };
+    st.version = CURRENT_VERSION;
+    Ok(st)
+}
+
+/// Moves a settings file that can't be read out of the way, next to it, so the user's
+/// settings can still be recovered by hand.
+pub fn back_up(path: &Path) -> Option<PathBuf> {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let name = path.file_name()?.to_string_lossy();
+    let backup = path.with_file_name(format!("{name}.unreadable-{stamp}"));
+    fs::rename(path, &backup).ok()?;
+    Some(backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::scratch;
+
+    fn fixture(name: &str) -> Vec<u8> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("fixtures/settings")
+            .join(name);
+        fs::read(&path).unwrap_or_else(|err| panic!("{}: {err}", path.display()))
+    }
+
+    #[test]
+    fn winit_settings_keep_their_values() {
+        let st = upgrade(&fixture("v0-winit.json")).unwrap();
+        assert_eq!(st.version, CURRENT_VERSION);
+        assert!(st.fit_on_open);
+        assert!(st.aspect_lock);
+        assert_eq!(st.last_file.as_deref(), Some("/Users/me/refs/pose.png"));
+        assert_eq!((st.window_w, st.window_h), (Some(640.0), Some(480.0)));
+        assert_eq!(st.window_size_units, Some(WindowSizeUnits::Logical));
+    }
+
+    #[test]
+    fn first_tauri_settings_parse() {
+        let st = upgrade(&fixture("v1-tauri-initial.json")).unwrap();
+        assert_eq!(st.version, CURRENT_VERSION);
+        assert!(!st.fit_on_open);
+        assert_eq!(st.last_file.as_deref(), Some("/Users/me/refs/hand.jpg"));
+        assert_eq!(st.window_size_units, Some(WindowSizeUnits::Physical));
+        assert!(st.windows.is_empty());
+    }
+
+    #[test]
+    fn unversioned_tauri_settings_keep_sessions() {
+        let st = upgrade(&fixture("v1-tauri-sessions.json")).unwrap();
+        assert_eq!(st.version, CURRENT_VERSION);
+        assert!(st.wrap_navigation);
+        assert_eq!(st.opacity, Some(80));
+        assert_eq!(
+            st.keybindings.get("fit_now").map(String::as_str),
+            Some("Ctrl+Shift+F")
+        );
+        let main = &st.windows["main"];
+        assert_eq!(main.files.len(), 2);
+        assert_eq!(main.active, 1);
+        assert_eq!(main.opacity, Some(80));
+    }
+
+    #[test]
+    fn current_settings_round_trip() {
+        let st = upgrade(&fixture("v2.json")).unwrap();
+        assert_eq!(st.version, CURRENT_VERSION);
+        assert_eq!(st.fit_mode, crate::fit::FitMode::ActualPixels);
+        assert_eq!(st.transforms["/Users/me/refs/pose.png"].rotation, 90);
+        let again = upgrade(&serde_json::to_vec(&st).unwrap()).unwrap();
+        assert_eq!(again.transforms, st.transforms);
+        assert_eq!(again.fit_mode, st.fit_mode);
+    }
+
+    #[test]
+    fn newer_versions_are_refused() {
+        assert!(matches!(
+            upgrade(&fixture("future.json")),
+            Err(Error::SettingsTooNew(99))
+        ));
+    }
+
+    #[test]
+    fn damaged_files_are_errors() {
+        assert!(upgrade(&fixture("truncated.json")).is_err());
+        assert!(upgrade(&fixture("wrong-type.json")).is_err());
+        assert!(upgrade(b"[]").is_err());
+    }
+
+    #[test]
+    fn backup_moves_the_file_aside() {
+        let dir = scratch("migrate", "backup");
+        let path = dir.join("settings.json");
+        fs::write(&path, fixture("truncated.json")).unwrap();
+        let backup = back_up(&path).unwrap();
+        assert!(!path.exists());
+        assert_eq!(fs::read(&backup).unwrap(), fixture("truncated.json"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
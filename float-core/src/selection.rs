@@ -0,0 +1,332 @@
+//! Selections: the files a window steps through and which one it shows, built from
+//! folders or from a single file's siblings.
+
+use std::{cmp::Ordering, fs, path::Path};
+
+/// The files a window can show, in order, and the one it shows.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SelectionState {
+    pub files: Vec<String>,
+    pub active: usize,
+}
+
+impl SelectionState {
+    pub fn active_file(&self) -> Option<&str> {
+        self.files.get(self.active).map(String::as_str)
+    }
+
+    /// Moves `delta` files along, stopping at the ends or wrapping around when `wrap`.
+    /// Returns whether the active file changed.
+    pub fn step(&mut self, delta: isize, wrap: bool) -> bool {
+        let len = self.files.len();
+        if len == 0 {
+            return false;
+        }
+        let next = (self.active as isize).saturating_add(delta);
+        let bounded = if wrap {
+            next.rem_euclid(len as isize) as usize
+        } else {
+            next.clamp(0, (len as isize) - 1) as usize
+        };
+        let changed = bounded != self.active;
+        self.active = bounded;
+        changed
+    }
+
+    /// Adds the images among `files` that aren't selected yet and makes the first of
+    /// them active. Returns whether any were added.
+    pub fn append(&mut self, files: impl IntoIterator<Item = String>) -> bool {
+        let first_new = self.files.len();
+        for file in files.into_iter().filter(|p| is_image_path(p)) {
+            if !self.files.contains(&file) {
+                self.files.push(file);
+            }
+        }
+        if self.files.len() == first_new {
+            return false;
+        }
+        self.active = first_new;
+        true
+    }
+}
+
+/// Whether `path` has the extension of an image Float can show.
+pub fn is_image_path(path: &str) -> bool {
+    let ext = Path::new(path)
+        .extension()
+        .and_then(|s| s.to_str())
+        .map(|s| s.to_ascii_lowercase());
+    matches!(
+        ext.as_deref(),
+        Some("png")
+            | Some("jpg")
+            | Some("jpeg")
+            | Some("gif")
+            | Some("webp")
+            | Some("bmp")
+            | Some("tif")
+            | Some("tiff")
+            | Some("heic")
+    )
+}
+
+/// Compares strings case-insensitively, treating runs of digits as numbers so
+/// `shot-2.png` sorts before `shot-10.png`.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let mut run_a = String::new();
+                while let Some(c) = a.peek().copied().filter(char::is_ascii_digit) {
+                    run_a.push(c);
+                    a.next();
+                }
+                let mut run_b = String::new();
+                while let Some(c) = b.peek().copied().filter(char::is_ascii_digit) {
+                    run_b.push(c);
+                    b.next();
+                }
+                let trimmed_a = run_a.trim_start_matches('0');
+                let trimmed_b = run_b.trim_start_matches('0');
+                let ord = trimmed_a
+                    .len()
+                    .cmp(&trimmed_b.len())
+                    .then_with(|| trimmed_a.cmp(trimmed_b))
+                    .then_with(|| run_a.len().cmp(&run_b.len()));
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let ord = x.to_lowercase().cmp(y.to_lowercase());
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+fn collect_into(dir: &Path, recursive: bool, out: &mut Vec<String>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        // Skip dotfiles, including macOS `._*` resource forks that look like images.
+        if name.to_string_lossy().starts_with('.') {
+            continue;
+        }
+        // `file_type` doesn't follow symlinks, so symlinked folders can't loop.
+        let file_type = match entry.file_type() {
+            Ok(file_type) => file_type,
+            Err(_) => continue,
+        };
+        let path = entry.path();
+        if file_type.is_dir() {
+            if recursive {
+                collect_into(&path, recursive, out);
+            }
+        } else {
+            let path_str = path.to_string_lossy().to_string();
+            if is_image_path(&path_str) {
+                out.push(path_str);
+            }
+        }
+    }
+}
+
+/// Every image in `dir` (and its subfolders when `recursive`), naturally sorted.
+pub fn images_in_folder(dir: &Path, recursive: bool) -> Vec<String> {
+    let mut files = Vec::new();
+    collect_into(dir, recursive, &mut files);
+    files.sort_by(|a, b| natural_cmp(a, b));
+    files
+}
+
+/// Selection over the images next to `file`, with `file` active. Falls back to a
+/// single-file selection when the folder can't be read.
+pub fn with_siblings(file: &str) -> SelectionState {
+    let siblings = Path::new(file)
+        .parent()
+        .map(|dir| images_in_folder(dir, false))
+        .unwrap_or_default();
+    match siblings.iter().position(|p| p == file) {
+        Some(active) => SelectionState {
+            files: siblings,
+            active,
+        },
+        None => SelectionState {
+            files: vec![file.to_string()],
+            active: 0,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::scratch;
+
+    fn selection(n: usize, active: usize) -> SelectionState {
+        SelectionState {
+            files: (0..n).map(|i| format!("/refs/{i}.png")).collect(),
+            active,
+        }
+    }
+
+    fn touch(path: &Path) {
+        fs::write(path, b"").unwrap();
+    }
+
+    #[test]
+    fn image_extensions_ignore_case() {
+        assert!(is_image_path("/refs/pose.PNG"));
+        assert!(is_image_path("shot.JpEg"));
+        assert!(is_image_path("scan.tif"));
+        assert!(is_image_path("phone.heic"));
+        assert!(!is_image_path("notes.txt"));
+        assert!(!is_image_path("png"));
+        assert!(!is_image_path("/refs/.png"));
+    }
+
+    #[test]
+    fn natural_order() {
+        let mut names = vec![
+            "shot-10.png",
+            "Shot-2.png",
+            "shot-1.png",
+            "a.png",
+            "shot-02.png",
+        ];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(
+            names,
+            [
+                "a.png",
+                "shot-1.png",
+                "Shot-2.png",
+                "shot-02.png",
+                "shot-10.png"
+            ]
+        );
+        assert_eq!(natural_cmp("img", "img1"), Ordering::Less);
+        assert_eq!(natural_cmp("IMG.png", "img.png"), Ordering::Equal);
+        assert_eq!(
+            natural_cmp(
+                "99999999999999999999999.png",
+                "100000000000000000000000.png"
+            ),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn step_stops_at_the_ends() {
+        let mut sel = selection(3, 0);
+        assert!(!sel.step(-1, false));
+        assert_eq!(sel.active, 0);
+        assert!(sel.step(1, false));
+        assert!(sel.step(5, false));
+        assert_eq!(sel.active, 2);
+        assert!(!sel.step(1, false));
+    }
+
+    #[test]
+    fn step_wraps_around() {
+        let mut sel = selection(3, 0);
+        assert!(sel.step(-1, true));
+        assert_eq!(sel.active, 2);
+        assert!(sel.step(1, true));
+        assert_eq!(sel.active, 0);
+        assert!(!sel.step(3, true));
+        assert!(sel.step(isize::MAX, true));
+    }
+
+    #[test]
+    fn step_on_an_empty_selection() {
+        let mut sel = SelectionState::default();
+        assert!(!sel.step(1, true));
+        assert_eq!(sel.active_file(), None);
+    }
+
+    #[test]
+    fn append_skips_duplicates_and_other_files() {
+        let mut sel = selection(2, 0);
+        let added = sel.append(vec![
+            "/refs/1.png".to_string(),
+            "/refs/notes.txt".to_string(),
+            "/refs/new.jpg".to_string(),
+            "/refs/new.jpg".to_string(),
+        ]);
+        assert!(added);
+        assert_eq!(sel.files.len(), 3);
+        assert_eq!(sel.active_file(), Some("/refs/new.jpg"));
+        assert!(!sel.append(vec!["/refs/0.png".to_string()]));
+        assert_eq!(sel.active, 2);
+    }
+
+    #[test]
+    fn folder_images_are_sorted_and_filtered() {
+        let dir = scratch("selection", "folder");
+        for name in [
+            "b-10.png",
+            "b-9.jpg",
+            ".hidden.png",
+            "._b-9.jpg",
+            "readme.md",
+        ] {
+            touch(&dir.join(name));
+        }
+        fs::create_dir(dir.join("sub")).unwrap();
+        touch(&dir.join("sub/a.gif"));
+        let names = |files: Vec<String>| -> Vec<String> {
+            files
+                .iter()
+                .map(|f| f.strip_prefix(dir.to_str().unwrap()).unwrap().to_string())
+                .collect()
+        };
+        let sep = std::path::MAIN_SEPARATOR;
+        assert_eq!(
+            names(images_in_folder(&dir, false)),
+            [format!("{sep}b-9.jpg"), format!("{sep}b-10.png")]
+        );
+        assert_eq!(
+            names(images_in_folder(&dir, true)),
+            [
+                format!("{sep}b-9.jpg"),
+                format!("{sep}b-10.png"),
+                format!("{sep}sub{sep}a.gif")
+            ]
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn siblings_select_the_opened_file() {
+        let dir = scratch("selection", "siblings");
+        for name in ["1.png", "2.png", "3.png"] {
+            touch(&dir.join(name));
+        }
+        let opened = dir.join("2.png").to_string_lossy().into_owned();
+        let sel = with_siblings(&opened);
+        assert_eq!(sel.files.len(), 3);
+        assert_eq!(sel.active_file(), Some(opened.as_str()));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn siblings_of_a_missing_file() {
+        let sel = with_siblings("/no/such/folder/pose.png");
+        assert_eq!(sel.files, ["/no/such/folder/pose.png"]);
+        assert_eq!(sel.active, 0);
+    }
+}
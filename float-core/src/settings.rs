@@ -0,0 +1,182 @@
+//! The shape of `settings.json`. [`PersistedState`] is what the current schema version
+//! reads and writes; older files are upgraded into it by [`crate::migrate`].
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{fit::FitMode, transform::ImageTransform};
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowSizeUnits {
+    Logical,
+    Physical,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PersistedState {
+    #[serde(default)]
+    pub version: u32, // schema version, see migrate.rs
+    pub last_file: Option<String>,
+    pub aspect_lock: bool,
+    pub window_w: Option<f64>,
+    pub window_h: Option<f64>,
+    pub window_size_units: Option<WindowSizeUnits>,
+    #[serde(default)]
+    pub window_x: Option<f64>, // logical outer position on `window_monitor`
+    #[serde(default)]
+    pub window_y: Option<f64>,
+    #[serde(default)]
+    pub window_monitor: Option<MonitorId>,
+    #[serde(default)]
+    pub folder_recursive: bool, // Open Folder… includes subfolders
+    #[serde(default)]
+    pub wrap_navigation: bool, // Previous/Next wrap around at the ends
+    #[serde(default)]
+    pub browse_siblings: bool, // opening one file selects every image in its folder
+    #[serde(default)]
+    pub opacity: Option<u8>, // most recent opacity choice, new windows start from it
+    #[serde(default)]
+    pub global_shortcuts: BTreeMap<HotkeyAction, String>, // overrides, "" turns one off
+    #[serde(default)]
+    pub keybindings: BTreeMap<String, String>, // menu shortcut overrides by menu id, "" removes one
+    #[serde(default)]
+    pub fit_mode: FitMode, // what Fit to Image sizes the window to
+    #[serde(default)]
+    pub fit_on_open: bool, // fit with `fit_mode` whenever a window's active file changes
+    #[serde(default)]
+    pub transforms: BTreeMap<String, ImageTransform>, // rotation and flip by file path
+    #[serde(default)]
+    pub windows: BTreeMap<String, WindowSession>, // per-window session, restored on launch
+}
+
+/// A window's saved state: what it shows and where it sits.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct WindowSession {
+    #[serde(default)]
+    pub files: Vec<String>,
+    #[serde(default)]
+    pub active: usize,
+    pub width: Option<f64>, // logical outer size
+    pub height: Option<f64>,
+    pub x: Option<f64>, // logical outer position on `monitor`
+    pub y: Option<f64>,
+    pub monitor: Option<MonitorId>,
+    pub opacity: Option<u8>,
+    pub aspect_lock: Option<bool>, // None follows the global default
+    #[serde(default)]
+    pub click_through: bool,
+    #[serde(default)]
+    pub view: Option<ViewState>, // zoom and pan of the active image
+}
+
+/// The monitor a window was on, matched by name and scale when restoring.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct MonitorId {
+    pub name: Option<String>,
+    pub scale: f64,
+}
+
+/// Zoom and pan of the image inside a window, owned by the frontend.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ViewState {
+    pub zoom: f64, // 1.0 is the image fitted to the window
+    pub x: f64,    // pan offset from the center, logical px
+    pub y: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyAction {
+    ShowHideAll,
+    ClickThrough,
+    NextImage,
+    PreviousImage,
+    AlwaysOnTop,
+}
+
+impl HotkeyAction {
+    pub const ALL: [HotkeyAction; 5] = [
+        HotkeyAction::ShowHideAll,
+        HotkeyAction::ClickThrough,
+        HotkeyAction::NextImage,
+        HotkeyAction::PreviousImage,
+        HotkeyAction::AlwaysOnTop,
+    ];
+
+    pub fn default_accelerator(self) -> &'static str {
+        match self {
+            HotkeyAction::ShowHideAll => "CommandOrControl+Shift+Alt+F",
+            HotkeyAction::ClickThrough => "CommandOrControl+Alt+G",
+            HotkeyAction::NextImage => "CommandOrControl+Shift+Alt+]",
+            HotkeyAction::PreviousImage => "CommandOrControl+Shift+Alt+[",
+            HotkeyAction::AlwaysOnTop => "CommandOrControl+Shift+Alt+T",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_fields_take_their_defaults() {
+        let st: PersistedState = serde_json::from_str(r#"{"aspect_lock": false}"#).unwrap();
+        assert_eq!(st.version, 0);
+        assert!(st.last_file.is_none());
+        assert!(!st.aspect_lock);
+        assert_eq!(st.fit_mode, FitMode::Aspect);
+        assert!(st.windows.is_empty());
+    }
+
+    #[test]
+    fn enums_use_their_file_names() {
+        let st = PersistedState {
+            window_size_units: Some(WindowSizeUnits::Logical),
+            fit_mode: FitMode::ActualPixels,
+            global_shortcuts: BTreeMap::from([(HotkeyAction::ShowHideAll, String::new())]),
+            ..Default::default()
+        };
+        let value = serde_json::to_value(&st).unwrap();
+        assert_eq!(value["window_size_units"], "logical");
+        assert_eq!(value["fit_mode"], "actual_pixels");
+        assert_eq!(value["global_shortcuts"]["show_hide_all"], "");
+    }
+
+    #[test]
+    fn sessions_round_trip() {
+        let session = WindowSession {
+            files: vec!["/a.png".into(), "/b.png".into()],
+            active: 1,
+            monitor: Some(MonitorId {
+                name: Some("DELL U2720Q".into()),
+                scale: 2.0,
+            }),
+            view: Some(ViewState {
+                zoom: 2.5,
+                x: -10.0,
+                y: 4.0,
+            }),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&session).unwrap();
+        let back: WindowSession = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.files, session.files);
+        assert_eq!(back.active, 1);
+        assert_eq!(back.monitor, session.monitor);
+        assert_eq!(back.view, session.view);
+        assert_eq!(back.aspect_lock, None);
+    }
+
+    #[test]
+    fn every_hotkey_has_its_own_default() {
+        let mut accelerators: Vec<_> = HotkeyAction::ALL
+            .iter()
+            .map(|a| a.default_accelerator())
+            .collect();
+        accelerators.sort_unstable();
+        accelerators.dedup();
+        assert_eq!(accelerators.len(), HotkeyAction::ALL.len());
+    }
+}
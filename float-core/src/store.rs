@@ -0,0 +1,490 @@
+//! Where settings are kept. Shells load and save through [`SettingsStore`]:
+//!
+//! - [`FileStore`] is `settings.json`. Each write goes to a temp file that is renamed
+//!   over the settings while holding an advisory lock on `settings.json.lock`, so a crash
+//!   mid-write or a second Float process can't leave a truncated file behind.
+//! - [`QueuedStore`] wraps another store and saves on one background thread, so saves
+//!   from menu handlers, window events and debounced size saves never block the UI, a
+//!   burst of them becomes a single write, and two writes never interleave.
+//! - [`MemoryStore`] keeps settings in memory, for tests and headless runs.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+use parking_lot::{Condvar, Mutex};
+
+use crate::{migrate, settings::PersistedState, Error};
+
+pub trait SettingsStore: Send + Sync {
+    /// The saved settings upgraded to the current version, or `None` when none were saved.
+    fn load(&self) -> Result<Option<PersistedState>, Error>;
+
+    /// Saves `st` as the current version.
+    fn save(&self, st: &PersistedState) -> Result<(), Error>;
+
+    /// Forgets the saved settings.
+    fn clear(&self) -> Result<(), Error>;
+}
+
+/// Settings in a JSON file.
+#[derive(Clone, Debug)]
+pub struct FileStore {
+    path: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(path: impl Into<PathBuf>) -> FileStore {
+        FileStore { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Moves an unreadable settings file aside; see [`migrate::back_up`].
+    pub fn back_up(&self) -> Option<PathBuf> {
+        migrate::back_up(&self.path)
+    }
+}
+
+impl SettingsStore for FileStore {
+    fn load(&self) -> Result<Option<PersistedState>, Error> {
+        match fs::read(&self.path) {
+            Ok(bytes) => migrate::upgrade(&bytes).map(Some),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(Error::io(&self.path, err)),
+        }
+    }
+
+    fn save(&self, st: &PersistedState) -> Result<(), Error> {
+        let mut st = st.clone();
+        st.version = migrate::CURRENT_VERSION;
+        let bytes = serde_json::to_vec_pretty(&st)?;
+        write_atomic(&self.path, &bytes).map_err(|err| Error::io(&self.path, err))?;
+        log::debug!(
+            target: "settings",
+            "saved path={} bytes={}",
+            self.path.display(),
+            bytes.len()
+        );
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<(), Error> {
+        remove(&self.path).map_err(|err| Error::io(&self.path, err))
+    }
+}
+
+/// Settings kept in memory.
+#[derive(Default)]
+pub struct MemoryStore {
+    saved: Mutex<Option<PersistedState>>,
+}
+
+impl SettingsStore for MemoryStore {
+    fn load(&self) -> Result<Option<PersistedState>, Error> {
+        Ok(self.saved.lock().clone())
+    }
+
+    fn save(&self, st: &PersistedState) -> Result<(), Error> {
+        let mut st = st.clone();
+        st.version = migrate::CURRENT_VERSION;
+        *self.saved.lock() = Some(st);
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<(), Error> {
+        self.saved.lock().take();
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct Queue {
+    pending: Option<PersistedState>, // only the latest save is kept
+    writing: bool,
+}
+
+type ErrorHandler = Box<dyn Fn(Error) + Send + Sync>;
+
+/// Saves to `S` from a background thread. [`SettingsStore::save`] only queues; loading
+/// and clearing first wait for the queue so they never race a write.
+pub struct QueuedStore<S> {
+    inner: S,
+    queue: Mutex<Queue>,
+    wake: Condvar,
+    idle: Condvar,
+    on_error: Mutex<Option<ErrorHandler>>, // failed saves are only logged without one
+    synchronous: AtomicBool,               // no writer thread, so saves go straight to `inner`
+}
+
+impl<S: SettingsStore + 'static> QueuedStore<S> {
+    /// Starts the writer thread. Without one, saves are written before `save` returns.
+    pub fn start(inner: S) -> Arc<QueuedStore<S>> {
+        QueuedStore::start_with(inner, |worker| {
+            thread::Builder::new()
+                .name("settings-writer".into())
+                .spawn(move || worker.run())
+                .map(drop)
+        })
+    }
+
+    fn start_with(
+        inner: S,
+        spawn: impl FnOnce(Arc<QueuedStore<S>>) -> io::Result<()>,
+    ) -> Arc<QueuedStore<S>> {
+        let store = Arc::new(QueuedStore {
+            inner,
+            queue: Mutex::new(Queue::default()),
+            wake: Condvar::new(),
+            idle: Condvar::new(),
+            on_error: Mutex::new(None),
+            synchronous: AtomicBool::new(false),
+        });
+        if let Err(err) = spawn(store.clone()) {
+            log::error!(target: "settings", "can't start the writer thread, saving inline: {err}");
+            store.synchronous.store(true, Ordering::SeqCst);
+        }
+        store
+    }
+
+    fn run(&self) {
+        loop {
+            let st = {
+                let mut queue = self.queue.lock();
+                let job = loop {
+                    match queue.pending.take() {
+                        Some(job) => break job,
+                        None => self.wake.wait(&mut queue),
+                    }
+                };
+                queue.writing = true;
+                job
+            };
+            if let Err(err) = self.inner.save(&st) {
+                match self.on_error.lock().as_ref() {
+                    Some(handler) => handler(err),
+                    None => log::error!(target: "settings", "save failed: {err}"),
+                }
+            }
+            self.queue.lock().writing = false;
+            self.idle.notify_all();
+        }
+    }
+}
+
+impl<S> QueuedStore<S> {
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Calls `handler` from the writer thread whenever a save fails.
+    pub fn on_error(&self, handler: impl Fn(Error) + Send + Sync + 'static) {
+        *self.on_error.lock() = Some(Box::new(handler));
+    }
+
+    /// Drops a save that hasn't started yet.
+    pub fn discard(&self) {
+        self.queue.lock().pending = None;
+        self.idle.notify_all();
+    }
+
+    /// Blocks until every queued save is done.
+    pub fn flush(&self) {
+        let mut queue = self.queue.lock();
+        while queue.pending.is_some() || queue.writing {
+            self.idle.wait(&mut queue);
+        }
+    }
+}
+
+impl<S: SettingsStore> SettingsStore for QueuedStore<S> {
+    fn load(&self) -> Result<Option<PersistedState>, Error> {
+        self.flush();
+        self.inner.load()
+    }
+
+    /// Queues `st`, replacing a save that hasn't started yet.
+    fn save(&self, st: &PersistedState) -> Result<(), Error> {
+        if self.synchronous.load(Ordering::SeqCst) {
+            return self.inner.save(st);
+        }
+        let replaced = self.queue.lock().pending.replace(st.clone()).is_some();
+        if replaced {
+            log::trace!(target: "settings", "coalesced a pending save");
+        }
+        self.wake.notify_one();
+        Ok(())
+    }
+
+    /// Drops queued saves so a stale one can't recreate the settings afterwards.
+    fn clear(&self) -> Result<(), Error> {
+        self.discard();
+        self.flush();
+        self.inner.clear()
+    }
+}
+
+/// Holds the advisory lock shared by every Float process until dropped.
+fn lock(path: &Path) -> io::Result<File> {
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(sibling(path, "lock"))?;
+    file.lock()?;
+    Ok(file)
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!("{name}.{suffix}"))
+}
+
+/// Replaces `path` with `bytes` in one step: readers see the old file or the new one,
+/// never a partial write.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let _lock = lock(path)?;
+    let tmp = sibling(path, &format!("tmp-{}", std::process::id()));
+    let written = File::create(&tmp).and_then(|mut file| {
+        file.write_all(bytes)?;
+        file.sync_all()
+    });
+    match written.and_then(|()| fs::rename(&tmp, path)) {
+        Ok(()) => Ok(()),
+        Err(err) => {
+            let _ = fs::remove_file(&tmp);
+            Err(err)
+        }
+    }
+}
+
+/// Deletes `path` under the same lock as writes.
+pub fn remove(path: &Path) -> io::Result<()> {
+    let _lock = lock(path)?;
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::atomic::AtomicUsize, time::Duration};
+
+    use super::*;
+    use crate::test_util::scratch;
+
+    fn settings(last_file: &str) -> PersistedState {
+        PersistedState {
+            last_file: Some(last_file.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Counts saves and holds each one until `gate` is released.
+    #[derive(Default)]
+    struct SlowStore {
+        saves: AtomicUsize,
+        gate: Mutex<()>,
+        last: MemoryStore,
+    }
+
+    impl SettingsStore for SlowStore {
+        fn load(&self) -> Result<Option<PersistedState>, Error> {
+            self.last.load()
+        }
+
+        fn save(&self, st: &PersistedState) -> Result<(), Error> {
+            let _gate = self.gate.lock();
+            self.saves.fetch_add(1, Ordering::SeqCst);
+            self.last.save(st)
+        }
+
+        fn clear(&self) -> Result<(), Error> {
+            self.last.clear()
+        }
+    }
+
+    /// Waits for the writer to pick up the queued save.
+    fn wait_until_taken<S>(store: &QueuedStore<S>) {
+        while store.queue.lock().pending.is_some() {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn file_round_trip() {
+        let dir = scratch("store", "round-trip");
+        let store = FileStore::new(dir.join("settings.json"));
+        assert!(store.load().unwrap().is_none());
+        store.save(&settings("/refs/a.png")).unwrap();
+        let st = store.load().unwrap().unwrap();
+        assert_eq!(st.last_file.as_deref(), Some("/refs/a.png"));
+        assert_eq!(st.version, migrate::CURRENT_VERSION);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn writes_leave_no_temp_files() {
+        let dir = scratch("store", "temp-files");
+        let store = FileStore::new(dir.join("settings.json"));
+        store.save(&settings("/refs/a.png")).unwrap();
+        store.save(&settings("/refs/b.png")).unwrap();
+        let mut names: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["settings.json", "settings.json.lock"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn clear_removes_the_file() {
+        let dir = scratch("store", "clear");
+        let store = FileStore::new(dir.join("settings.json"));
+        store.save(&settings("/refs/a.png")).unwrap();
+        store.clear().unwrap();
+        assert!(!store.path().exists());
+        assert!(store.load().unwrap().is_none());
+        store.clear().unwrap(); // nothing left to remove
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn unreadable_files_are_errors_and_can_be_backed_up() {
+        let dir = scratch("store", "unreadable");
+        let store = FileStore::new(dir.join("settings.json"));
+        fs::write(store.path(), b"{\"last_file\": ").unwrap();
+        assert!(store.load().is_err());
+        let backup = store.back_up().unwrap();
+        assert!(backup.exists());
+        assert!(store.load().unwrap().is_none());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn saving_into_a_missing_folder_fails() {
+        let dir = scratch("store", "missing");
+        let store = FileStore::new(dir.join("gone/settings.json"));
+        assert!(matches!(
+            store.save(&settings("/refs/a.png")),
+            Err(Error::Io { .. })
+        ));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn memory_store() {
+        let store = MemoryStore::default();
+        assert!(store.load().unwrap().is_none());
+        store.save(&settings("/refs/a.png")).unwrap();
+        let st = store.load().unwrap().unwrap();
+        assert_eq!(st.last_file.as_deref(), Some("/refs/a.png"));
+        assert_eq!(st.version, migrate::CURRENT_VERSION);
+        store.clear().unwrap();
+        assert!(store.load().unwrap().is_none());
+    }
+
+    #[test]
+    fn queued_saves_coalesce() {
+        let store = QueuedStore::start(SlowStore::default());
+        let gate = store.inner().gate.lock();
+        store.save(&settings("/refs/0.png")).unwrap();
+        // Let the writer pick up the first save and block on the gate.
+        wait_until_taken(&store);
+        for i in 1..=10 {
+            store.save(&settings(&format!("/refs/{i}.png"))).unwrap();
+        }
+        drop(gate);
+        let st = store.load().unwrap().unwrap();
+        assert_eq!(st.last_file.as_deref(), Some("/refs/10.png"));
+        assert_eq!(store.inner().saves.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn clear_drops_queued_saves() {
+        let store = QueuedStore::start(SlowStore::default());
+        let gate = store.inner().gate.lock();
+        store.save(&settings("/refs/0.png")).unwrap();
+        wait_until_taken(&store);
+        store.save(&settings("/refs/1.png")).unwrap();
+        store.discard();
+        drop(gate);
+        let st = store.load().unwrap().unwrap();
+        assert_eq!(st.last_file.as_deref(), Some("/refs/0.png"));
+        assert_eq!(store.inner().saves.load(Ordering::SeqCst), 1);
+        store.save(&settings("/refs/2.png")).unwrap();
+        store.clear().unwrap();
+        assert!(store.load().unwrap().is_none());
+    }
+
+    #[test]
+    fn failed_saves_reach_the_handler() {
+        let dir = scratch("store", "handler");
+        let store = QueuedStore::start(FileStore::new(dir.join("gone/settings.json")));
+        let failures = Arc::new(AtomicUsize::new(0));
+        let seen = failures.clone();
+        store.on_error(move |err| {
+            assert!(matches!(err, Error::Io { .. }));
+            seen.fetch_add(1, Ordering::SeqCst);
+        });
+        store.save(&settings("/refs/a.png")).unwrap();
+        store.flush();
+        assert_eq!(failures.load(Ordering::SeqCst), 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn saves_are_written_inline_without_a_writer_thread() {
+        let dir = scratch("store", "no-writer");
+        let store = QueuedStore::start_with(FileStore::new(dir.join("settings.json")), |_| {
+            Err(io::Error::other("no threads"))
+        });
+        store.save(&settings("/refs/a.png")).unwrap();
+        let on_disk = FileStore::new(dir.join("settings.json"))
+            .load()
+            .unwrap()
+            .unwrap();
+        assert_eq!(on_disk.last_file.as_deref(), Some("/refs/a.png"));
+        // Nothing is queued, so these return instead of waiting on a writer.
+        store.flush();
+        assert!(store.load().unwrap().is_some());
+        store.clear().unwrap();
+        assert!(store.load().unwrap().is_none());
+        let gone = QueuedStore::start_with(FileStore::new(dir.join("gone/settings.json")), |_| {
+            Err(io::Error::other("no threads"))
+        });
+        assert!(matches!(
+            gone.save(&settings("/refs/a.png")),
+            Err(Error::Io { .. })
+        ));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn queued_files_are_written_by_flush() {
+        let dir = scratch("store", "flush");
+        let store = QueuedStore::start(FileStore::new(dir.join("settings.json")));
+        store.save(&settings("/refs/a.png")).unwrap();
+        store.flush();
+        let on_disk = FileStore::new(dir.join("settings.json"))
+            .load()
+            .unwrap()
+            .unwrap();
+        assert_eq!(on_disk.last_file.as_deref(), Some("/refs/a.png"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
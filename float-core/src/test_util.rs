@@ -0,0 +1,12 @@
+//! Helpers shared by the unit tests, here and in the shells (`test-util` feature).
+
+use std::{fs, path::PathBuf};
+
+/// A folder of its own under the temp dir for test `name` in module `module`,
+/// emptied first.
+pub fn scratch(module: &str, name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("float-{module}-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -0,0 +1,190 @@
+//! Rotation and mirroring of the displayed image. A transform is stored per file path
+//! so reopening the file shows it the way it was left.
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Mirror the image horizontally (if `mirrored`), then rotate it clockwise by
+/// `rotation` degrees. Every mix of quarter turns and flips reduces to this form, which
+/// is also the order the frontend applies them in CSS.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageTransform {
+    #[serde(default, deserialize_with = "deserialize_rotation")]
+    pub rotation: u16, // 0, 90, 180 or 270
+    #[serde(default)]
+    pub mirrored: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "snake_case")]
+pub enum TransformAction {
+    RotateCw,
+    RotateCcw,
+    FlipHorizontal,
+    FlipVertical,
+    Reset,
+}
+
+impl ImageTransform {
+    pub fn is_identity(self) -> bool {
+        self == ImageTransform::default()
+    }
+
+    /// Whether the image is shown on its side, so width and height trade places.
+    pub fn swaps_axes(self) -> bool {
+        self.rotation % 180 == 90
+    }
+
+    /// The transform after `action`, with flips taken along the screen's axes.
+    pub fn then(self, action: TransformAction) -> ImageTransform {
+        let rotation = u32::from(quarter_turn(self.rotation.into()));
+        match action {
+            TransformAction::RotateCw => ImageTransform {
+                rotation: quarter_turn(rotation + 90),
+                ..self
+            },
+            TransformAction::RotateCcw => ImageTransform {
+                rotation: quarter_turn(rotation + 270),
+                ..self
+            },
+            // Mirroring a rotated image reverses the direction of its rotation.
+            TransformAction::FlipHorizontal => ImageTransform {
+                rotation: quarter_turn(360 - rotation),
+                mirrored: !self.mirrored,
+            },
+            TransformAction::FlipVertical => ImageTransform {
+                rotation: quarter_turn(540 - rotation),
+                mirrored: !self.mirrored,
+            },
+            TransformAction::Reset => ImageTransform::default(),
+        }
+    }
+
+    /// Size of a `width`×`height` image once transformed.
+    pub fn apply_to(self, size: (u32, u32)) -> (u32, u32) {
+        if self.swaps_axes() {
+            (size.1, size.0)
+        } else {
+            size
+        }
+    }
+}
+
+/// `degrees` as 0, 90, 180 or 270: the nearest quarter turn, less full turns.
+fn quarter_turn(degrees: u32) -> u16 {
+    ((degrees % 360 + 45) / 90 % 4 * 90) as u16
+}
+
+/// Hand-edited settings may hold any angle; snap it so arithmetic on it stays in range.
+fn deserialize_rotation<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u16, D::Error> {
+    u32::deserialize(deserializer).map(quarter_turn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use TransformAction::*;
+
+    fn after(actions: &[TransformAction]) -> ImageTransform {
+        actions
+            .iter()
+            .fold(ImageTransform::default(), |t, &a| t.then(a))
+    }
+
+    #[test]
+    fn four_turns_are_identity() {
+        assert!(after(&[RotateCw; 4]).is_identity());
+        assert!(after(&[RotateCcw; 4]).is_identity());
+        assert!(after(&[RotateCw, RotateCcw]).is_identity());
+    }
+
+    #[test]
+    fn rotations_wrap_at_360() {
+        assert_eq!(after(&[RotateCcw]).rotation, 270);
+        assert_eq!(after(&[RotateCw, RotateCw, RotateCw]).rotation, 270);
+    }
+
+    #[test]
+    fn flipping_twice_is_identity() {
+        assert!(after(&[FlipHorizontal, FlipHorizontal]).is_identity());
+        assert!(after(&[FlipVertical, FlipVertical]).is_identity());
+        assert!(after(&[RotateCw, FlipVertical, FlipVertical, RotateCcw]).is_identity());
+    }
+
+    #[test]
+    fn both_flips_make_a_half_turn() {
+        let t = after(&[FlipHorizontal, FlipVertical]);
+        assert_eq!(
+            t,
+            ImageTransform {
+                rotation: 180,
+                mirrored: false
+            }
+        );
+    }
+
+    #[test]
+    fn flips_follow_the_screen_after_a_turn() {
+        // Turned on its side, flipping along the screen's horizontal axis mirrors what
+        // used to be the image's vertical axis.
+        let turned_then_flipped = after(&[RotateCw, FlipHorizontal]);
+        let flipped_then_turned = after(&[FlipVertical, RotateCw]);
+        assert_eq!(turned_then_flipped, flipped_then_turned);
+        assert_eq!(
+            turned_then_flipped,
+            ImageTransform {
+                rotation: 270,
+                mirrored: true
+            }
+        );
+    }
+
+    #[test]
+    fn reset_clears_everything() {
+        assert!(after(&[RotateCw, FlipVertical, Reset]).is_identity());
+    }
+
+    #[test]
+    fn quarter_turns_swap_the_size() {
+        assert_eq!(after(&[RotateCw]).apply_to((1600, 900)), (900, 1600));
+        assert_eq!(
+            after(&[RotateCw, RotateCw]).apply_to((1600, 900)),
+            (1600, 900)
+        );
+        assert_eq!(after(&[FlipHorizontal]).apply_to((1600, 900)), (1600, 900));
+        assert!(after(&[RotateCcw, FlipVertical]).swaps_axes());
+    }
+
+    #[test]
+    fn out_of_range_rotations_snap_to_a_quarter_turn() {
+        let read = |json: &str| serde_json::from_str::<ImageTransform>(json).unwrap();
+        assert_eq!(read(r#"{"rotation": 450}"#).rotation, 90);
+        assert_eq!(read(r#"{"rotation": 100}"#).rotation, 90);
+        assert_eq!(read(r#"{"rotation": 359}"#).rotation, 0);
+        assert_eq!(read(r#"{"rotation": 4294967295}"#).rotation, 270);
+
+        let unread = ImageTransform {
+            rotation: u16::MAX,
+            mirrored: false,
+        };
+        // 65535° is 15° past 182 full turns.
+        assert_eq!(unread.then(RotateCcw).rotation, 270);
+        assert_eq!(unread.then(FlipHorizontal).rotation, 0);
+        assert_eq!(unread.then(FlipVertical).rotation, 180);
+    }
+
+    #[test]
+    fn stored_transforms_read_back() {
+        let t = after(&[RotateCw, FlipHorizontal]);
+        let json = serde_json::to_string(&t).unwrap();
+        assert_eq!(serde_json::from_str::<ImageTransform>(&json).unwrap(), t);
+        assert!(serde_json::from_str::<ImageTransform>("{}")
+            .unwrap()
+            .is_identity());
+        assert_eq!(
+            serde_json::to_string(&FlipHorizontal).unwrap(),
+            "\"flip_horizontal\""
+        );
+    }
+}
@@ -0,0 +1,348 @@
+//! Command-line arguments: `float [OPTIONS] [PATHS]...` and `float ctl <ACTION>`.
+//!
+//! Parsing happens before Tauri starts so `--help`, `--version` and bad input print
+//! and exit without ever opening a window.
+
+use std::path::{self, PathBuf};
+
+use clap::{value_parser, Args, Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+
+use float_core::{fit::FitMode, selection::is_image_path, transform::TransformAction};
+
+#[derive(Parser, Clone, Debug, Serialize, Deserialize)]
+#[command(
+    name = "float",
+    version,
+    about = "Always-on-top image viewer",
+    args_conflicts_with_subcommands = true
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    #[serde(skip)]
+    pub command: Option<Command>,
+
+    /// Image files or folders to open
+    #[arg(value_name = "PATH", value_parser = existing_path)]
+    pub paths: Vec<PathBuf>,
+
+    /// Open each path in its own window
+    #[arg(short = 'w', long)]
+    pub separate_windows: bool,
+
+    /// Window size in logical pixels, e.g. 800x600
+    #[arg(long, value_name = "WxH", value_parser = parse_size)]
+    pub size: Option<(f64, f64)>,
+
+    /// Window position in logical pixels, e.g. 40,60 (negative values allowed)
+    #[arg(long, value_name = "X,Y", value_parser = parse_position, allow_hyphen_values = true)]
+    pub position: Option<(f64, f64)>,
+
+    /// Window opacity in percent
+    #[arg(long, value_name = "0-100", value_parser = value_parser!(u8).range(0..=100))]
+    pub opacity: Option<u8>,
+
+    /// Start with a single empty window instead of restoring the previous session
+    #[arg(long)]
+    pub no_restore: bool,
+
+    /// Fit each window to its image after opening
+    #[arg(long)]
+    pub fit: bool,
+
+    /// Lock the aspect ratio of the windows this launch opens
+    #[arg(long)]
+    pub aspect_lock: bool,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum Command {
+    /// Control the running Float from scripts and editor plugins
+    Ctl {
+        /// Print replies as JSON
+        #[arg(long, global = true)]
+        json: bool,
+
+        #[command(subcommand)]
+        action: CtlAction,
+    },
+}
+
+/// Target window for `float ctl`; defaults to the last focused one.
+#[derive(Args, Clone, Debug)]
+pub struct WindowArg {
+    /// Window label, as printed by `float ctl list`
+    #[arg(long, value_name = "LABEL")]
+    pub window: Option<String>,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum CtlAction {
+    /// Open files or folders in a window
+    Open {
+        #[command(flatten)]
+        target: WindowArg,
+
+        /// Open in a new window instead
+        #[arg(short, long, conflicts_with = "window")]
+        new_window: bool,
+
+        /// Image files or folders to open
+        #[arg(value_name = "PATH", required = true, value_parser = existing_path)]
+        paths: Vec<PathBuf>,
+    },
+    /// Show the next file in the window's selection
+    Next {
+        #[command(flatten)]
+        target: WindowArg,
+    },
+    /// Show the previous file in the window's selection
+    #[command(alias = "prev")]
+    Previous {
+        #[command(flatten)]
+        target: WindowArg,
+    },
+    /// Fit the window to its image
+    Fit {
+        #[command(flatten)]
+        target: WindowArg,
+
+        /// Fit mode; defaults to the one chosen in View → Fit Mode
+        #[arg(long, value_enum)]
+        mode: Option<FitMode>,
+    },
+    /// Turn a window's "Lock aspect ratio on resize" on, off or flip it
+    AspectLock {
+        #[command(flatten)]
+        target: WindowArg,
+
+        #[arg(value_enum, default_value_t = Switch::Toggle)]
+        state: Switch,
+    },
+    /// Set a window's opacity in percent
+    Opacity {
+        #[command(flatten)]
+        target: WindowArg,
+
+        #[arg(value_name = "0-100", value_parser = value_parser!(u8).range(0..=100))]
+        percent: u8,
+    },
+    /// Rotate or flip a window's image; the result is remembered for the file
+    Transform {
+        #[command(flatten)]
+        target: WindowArg,
+
+        #[arg(value_enum)]
+        action: TransformAction,
+    },
+    /// Close a window
+    Close {
+        #[command(flatten)]
+        target: WindowArg,
+    },
+    /// List windows and their active files
+    List,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Switch {
+    On,
+    Off,
+    Toggle,
+}
+
+/// Per-window overrides applied to every window the command line opens.
+#[derive(Clone, Debug, Default)]
+pub struct WindowOptions {
+    pub size: Option<(f64, f64)>,
+    pub position: Option<(f64, f64)>,
+    pub opacity: Option<u8>,
+    pub fit: bool,
+    pub aspect_lock: bool,
+}
+
+impl Cli {
+    pub fn window_options(&self) -> WindowOptions {
+        WindowOptions {
+            size: self.size,
+            position: self.position,
+            opacity: self.opacity,
+            fit: self.fit,
+            aspect_lock: self.aspect_lock,
+        }
+    }
+}
+
+/// Parses argv, printing help/version or a usage error and exiting when needed
+/// (status 0 for help/version, 2 for bad input).
+pub fn parse() -> Cli {
+    attach_console();
+    Cli::parse()
+}
+
+/// Release builds on Windows are GUI-subsystem binaries with no console, so borrow the
+/// launching terminal's (if any) to make `--help` and errors visible.
+#[cfg(target_os = "windows")]
+fn attach_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn attach_console() {}
+
+pub fn existing_path(value: &str) -> Result<PathBuf, String> {
+    let path = path::absolute(value).map_err(|e| e.to_string())?;
+    if !path.exists() {
+        return Err("no such file or folder".into());
+    }
+    if path.is_file() && !is_image_path(value) {
+        return Err(
+            "not a supported image (png, jpg, jpeg, gif, webp, bmp, tif, tiff, heic)".into(),
+        );
+    }
+    Ok(path)
+}
+
+fn parse_pair(value: &str, separator: char, example: &str) -> Result<(f64, f64), String> {
+    let invalid = || format!("expected {example}");
+    let (a, b) = value.split_once(separator).ok_or_else(invalid)?;
+    let a: f64 = a.trim().parse().map_err(|_| invalid())?;
+    let b: f64 = b.trim().parse().map_err(|_| invalid())?;
+    if !a.is_finite() || !b.is_finite() {
+        return Err(invalid());
+    }
+    Ok((a, b))
+}
+
+fn parse_size(value: &str) -> Result<(f64, f64), String> {
+    let (w, h) = parse_pair(
+        &value.to_ascii_lowercase(),
+        'x',
+        "WIDTHxHEIGHT, e.g. 800x600",
+    )?;
+    if w < 50.0 || h < 50.0 {
+        return Err("width and height must be at least 50".into());
+    }
+    Ok((w, h))
+}
+
+fn parse_position(value: &str) -> Result<(f64, f64), String> {
+    parse_pair(value, ',', "X,Y, e.g. 40,60")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use float_core::test_util::scratch;
+
+    use super::*;
+
+    #[test]
+    fn sizes_take_either_x() {
+        assert_eq!(parse_size("800x600"), Ok((800.0, 600.0)));
+        assert_eq!(parse_size("800X600"), Ok((800.0, 600.0)));
+        assert_eq!(parse_size(" 480.5 x 320 "), Ok((480.5, 320.0)));
+    }
+
+    #[test]
+    fn sizes_below_the_minimum_are_rejected() {
+        assert!(parse_size("49x600").is_err());
+        assert!(parse_size("800x10").is_err());
+        assert!(parse_size("-800x600").is_err());
+        assert_eq!(parse_size("50x50"), Ok((50.0, 50.0)));
+    }
+
+    #[test]
+    fn sizes_must_be_two_finite_numbers() {
+        for bad in [
+            "800", "800,600", "800x", "x600", "NaNx600", "800xinf", "axb", "",
+        ] {
+            assert!(parse_size(bad).is_err(), "{bad:?} was accepted");
+        }
+    }
+
+    #[test]
+    fn positions_may_be_negative() {
+        assert_eq!(parse_position("40,60"), Ok((40.0, 60.0)));
+        assert_eq!(parse_position("-1920, -40"), Ok((-1920.0, -40.0)));
+        for bad in ["40x60", "40", "NaN,0", "0,-inf", ","] {
+            assert!(parse_position(bad).is_err(), "{bad:?} was accepted");
+        }
+    }
+
+    #[test]
+    fn paths_must_exist_and_be_images_or_folders() {
+        let dir = scratch("cli", "paths");
+        fs::write(dir.join("shot.PNG"), b"").unwrap();
+        fs::write(dir.join("notes.txt"), b"").unwrap();
+        let arg = |name: &str| dir.join(name).to_string_lossy().into_owned();
+
+        assert_eq!(existing_path(&arg("shot.PNG")), Ok(dir.join("shot.PNG")));
+        assert_eq!(existing_path(&arg("")), Ok(dir.clone()));
+        assert!(existing_path(&arg("notes.txt"))
+            .unwrap_err()
+            .starts_with("not a supported image"));
+        assert_eq!(
+            existing_path(&arg("gone.png")),
+            Err("no such file or folder".into())
+        );
+        assert!(existing_path(&arg("shot.PNG")).unwrap().is_absolute());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn launch_options_parse() {
+        let cli = Cli::try_parse_from([
+            "float",
+            "--size",
+            "480X320",
+            "--position",
+            "-40,-60",
+            "--opacity",
+            "80",
+            "--aspect-lock",
+        ])
+        .unwrap();
+        let options = cli.window_options();
+        assert_eq!(options.size, Some((480.0, 320.0)));
+        assert_eq!(options.position, Some((-40.0, -60.0)));
+        assert_eq!(options.opacity, Some(80));
+        assert!(options.aspect_lock);
+        assert!(cli.paths.is_empty());
+    }
+
+    #[test]
+    fn bad_launch_options_are_usage_errors() {
+        for args in [
+            &["float", "--size", "10x10"][..],
+            &["float", "--opacity", "101"],
+            &["float", "--position", "1,2,3"],
+            &["float", "/no/such/file.png"],
+        ] {
+            let err = Cli::try_parse_from(args).unwrap_err();
+            assert_eq!(err.exit_code(), 2, "{args:?}");
+        }
+    }
+
+    #[test]
+    fn ctl_commands_take_a_window() {
+        let cli =
+            Cli::try_parse_from(["float", "ctl", "aspect-lock", "on", "--window", "window-2"])
+                .unwrap();
+        match cli.command {
+            Some(Command::Ctl {
+                action: CtlAction::AspectLock { target, state },
+                json: false,
+            }) => {
+                assert_eq!(target.window.as_deref(), Some("window-2"));
+                assert_eq!(state, Switch::On);
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+        assert!(Cli::try_parse_from(["float", "ctl", "opacity", "150"]).is_err());
+    }
+}
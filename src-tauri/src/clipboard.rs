@@ -0,0 +1,77 @@
+//! Edit → Paste Image: stores clipboard image data as a PNG in the app cache so it
+//! goes through the normal selection path (fit, aspect lock, persistence).
+
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use image::{ImageFormat, RgbaImage};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::Error;
+
+fn paste_dir(app: &AppHandle) -> Result<PathBuf, Error> {
+    let dir = app
+        .path()
+        .app_cache_dir()
+        .map_err(|_| Error::NoCacheDir)?
+        .join("pasted");
+    if !dir.exists() {
+        fs::create_dir_all(&dir)?;
+    }
+    Ok(dir)
+}
+
+/// Writes the clipboard image to `pasted-<millis>.png` and returns its path.
+pub fn save_clipboard_image(app: &AppHandle) -> Result<PathBuf, Error> {
+    let image = app
+        .clipboard()
+        .read_image()
+        .map_err(|e| Error::Clipboard(e.to_string()))?;
+    let buffer = RgbaImage::from_raw(image.width(), image.height(), image.rgba().to_vec())
+        .ok_or_else(|| Error::Clipboard("clipboard image has an unexpected size".into()))?;
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    let path = paste_dir(app)?.join(format!("pasted-{stamp}.png"));
+    buffer.save_with_format(&path, ImageFormat::Png)?;
+    Ok(path)
+}
+
+/// Whether `path` is a paste living in the app cache (and so may disappear).
+pub fn is_pasted(app: &AppHandle, path: &Path) -> bool {
+    paste_dir(app)
+        .map(|dir| path.starts_with(dir))
+        .unwrap_or(false)
+}
+
+/// Deletes the stored pastes not in `keep`, returning how many went.
+pub fn prune(app: &AppHandle, keep: &HashSet<PathBuf>) -> Result<usize, Error> {
+    let mut removed = 0;
+    for entry in fs::read_dir(paste_dir(app)?)? {
+        let path = entry?.path();
+        let is_paste = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with("pasted-") && name.ends_with(".png"));
+        if is_paste && !keep.contains(&path) {
+            fs::remove_file(&path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Deletes every stored paste. A missing paste folder counts as cleared.
+pub fn clear(app: &AppHandle) -> Result<(), Error> {
+    let dir = paste_dir(app)?;
+    match fs::remove_dir_all(dir) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(()),
+    }
+}
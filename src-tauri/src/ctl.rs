@@ -0,0 +1,96 @@
+//! `float ctl`: a thin client that turns a subcommand into one control-socket request
+//! and prints the reply.
+
+use serde_json::Value;
+
+use crate::{
+    cli::{CtlAction, Switch},
+    ipc::{self, Request},
+};
+
+fn request_for(action: CtlAction) -> Request {
+    match action {
+        CtlAction::Open {
+            target,
+            new_window,
+            paths,
+        } => Request::Open {
+            paths,
+            window: target.window,
+            new_window,
+        },
+        CtlAction::Next { target } => Request::Next {
+            window: target.window,
+        },
+        CtlAction::Previous { target } => Request::Previous {
+            window: target.window,
+        },
+        CtlAction::Fit { target, mode } => Request::Fit {
+            window: target.window,
+            mode,
+        },
+        CtlAction::AspectLock { target, state } => Request::AspectLock {
+            window: target.window,
+            enabled: match state {
+                Switch::On => Some(true),
+                Switch::Off => Some(false),
+                Switch::Toggle => None,
+            },
+        },
+        CtlAction::Opacity { target, percent } => Request::Opacity {
+            window: target.window,
+            percent,
+        },
+        CtlAction::Transform { target, action } => Request::Transform {
+            window: target.window,
+            action,
+        },
+        CtlAction::Close { target } => Request::Close {
+            window: target.window,
+        },
+        CtlAction::List => Request::List,
+    }
+}
+
+/// One line per window: `label<TAB>index/total<TAB>path`, focused window marked `*`.
+fn print_window(window: &Value) {
+    let field = |name: &str| window.get(name).cloned().unwrap_or(Value::Null);
+    let label = field("label");
+    let marker = if field("focused") == Value::Bool(true) {
+        "*"
+    } else {
+        " "
+    };
+    let position = match (field("index").as_u64(), field("total").as_u64()) {
+        (Some(index), Some(total)) => format!("{}/{}", index + 1, total),
+        _ => "-".to_string(),
+    };
+    println!(
+        "{marker}{}\t{position}\t{}",
+        label.as_str().unwrap_or_default(),
+        field("path").as_str().unwrap_or_default()
+    );
+}
+
+/// Sends the action to the running instance and returns the process exit status.
+pub fn run(action: CtlAction, json: bool) -> i32 {
+    let response = match ipc::send(&request_for(action)) {
+        Ok(response) => response,
+        Err(_) => {
+            eprintln!("float: Float is not running");
+            return 1;
+        }
+    };
+    if !response.ok {
+        eprintln!("float: {}", response.error.unwrap_or_default());
+        return 1;
+    }
+    match (response.data, json) {
+        (Some(data), true) => println!("{data}"),
+        (Some(Value::Array(windows)), false) => windows.iter().for_each(print_window),
+        (Some(data), false) if data.get("label").is_some() => print_window(&data),
+        (Some(data), false) => println!("{data}"),
+        (None, _) => {}
+    }
+    0
+}
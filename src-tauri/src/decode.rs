@@ -0,0 +1,322 @@
+//! Decoding for image formats the webview can't display on every platform.
+//!
+//! PNG, JPEG, GIF, WebP and BMP are served to the webview untouched. TIFF and HEIC
+//! are decoded here and transcoded to PNG on demand; HEIC needs the `heic` cargo
+//! feature (system libheif), otherwise it reports `Error::CodecUnavailable`.
+//!
+//! Dimensions are reported after applying the EXIF orientation so callers filling
+//! the per-window aspect ratio see what the user sees.
+
+use std::{
+    fs::File,
+    io::{BufReader, Cursor},
+    path::Path,
+};
+
+use image::{DynamicImage, ImageError, ImageOutputFormat};
+
+use crate::Error;
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|s| s.to_str())
+        .map(|s| s.to_ascii_lowercase())
+}
+
+fn is_heic(path: &Path) -> bool {
+    matches!(extension(path).as_deref(), Some("heic"))
+}
+
+/// Maps "no decoder compiled in" to `CodecUnavailable` so callers can tell it apart
+/// from a corrupt file, and I/O failures to missing or unreadable files.
+fn image_error(path: &Path, err: ImageError) -> Error {
+    match err {
+        ImageError::Unsupported(_) => Error::CodecUnavailable(
+            extension(path)
+                .map(|ext| ext.to_ascii_uppercase())
+                .unwrap_or_else(|| "this format".into()),
+        ),
+        ImageError::IoError(err) => Error::io(path, err),
+        ImageError::Decoding(err) => Error::decode(path, err),
+        other => Error::Image(other),
+    }
+}
+
+/// Formats every supported webview renders without help.
+pub fn is_web_native(path: &Path) -> bool {
+    matches!(
+        extension(path).as_deref(),
+        Some("png") | Some("jpg") | Some("jpeg") | Some("gif") | Some("webp") | Some("bmp")
+    )
+}
+
+/// EXIF orientation (1..=8) of the file, or 1 when absent or unreadable.
+/// libheif already applies HEIC transforms on decode, so HEIC is always 1.
+pub fn orientation(path: &Path) -> u32 {
+    if is_heic(path) {
+        return 1;
+    }
+    let file = match File::open(path) {
+        Ok(f) => f,
+        Err(_) => return 1,
+    };
+    exif::Reader::new()
+        .read_from_container(&mut BufReader::new(file))
+        .ok()
+        .and_then(|data| {
+            data.get_field(exif::Tag::Orientation, exif::In::PRIMARY)
+                .and_then(|field| field.value.get_uint(0))
+        })
+        .filter(|o| (1..=8).contains(o))
+        .unwrap_or(1)
+}
+
+/// Orientations 5..=8 rotate by 90° and swap the displayed width and height.
+fn swaps_axes(orientation: u32) -> bool {
+    matches!(orientation, 5..=8)
+}
+
+fn apply_orientation(img: DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => img.fliph(),
+        3 => img.rotate180(),
+        4 => img.flipv(),
+        5 => img.rotate90().fliph(),
+        6 => img.rotate90(),
+        7 => img.rotate270().fliph(),
+        8 => img.rotate270(),
+        _ => img,
+    }
+}
+
+/// Displayed `(width, height)`, i.e. after EXIF orientation is applied.
+pub fn image_dimensions(path: &Path) -> Result<(u32, u32), Error> {
+    if is_heic(path) {
+        return heic::dimensions(path);
+    }
+    let (w, h) = image::image_dimensions(path).map_err(|e| image_error(path, e))?;
+    if swaps_axes(orientation(path)) {
+        Ok((h, w))
+    } else {
+        Ok((w, h))
+    }
+}
+
+/// Decodes the file with its EXIF orientation applied to the pixels.
+pub fn decode(path: &Path) -> Result<DynamicImage, Error> {
+    if is_heic(path) {
+        return heic::decode(path);
+    }
+    let img = image::open(path).map_err(|e| image_error(path, e))?;
+    Ok(apply_orientation(img, orientation(path)))
+}
+
+pub fn transcode_to_png(path: &Path) -> Result<Vec<u8>, Error> {
+    let img = decode(path)?;
+    let mut out = Cursor::new(Vec::new());
+    img.write_to(&mut out, ImageOutputFormat::Png)?;
+    Ok(out.into_inner())
+}
+
+#[cfg(feature = "heic")]
+mod heic {
+    use std::path::Path;
+
+    use image::{DynamicImage, RgbaImage};
+    use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
+
+    use crate::Error;
+
+    fn context(path: &Path) -> Result<HeifContext<'static>, Error> {
+        let name = path
+            .to_str()
+            .ok_or_else(|| Error::decode(path, "non UTF-8 path"))?;
+        HeifContext::read_from_file(name).map_err(|e| Error::decode(path, e))
+    }
+
+    pub fn dimensions(path: &Path) -> Result<(u32, u32), Error> {
+        let ctx = context(path)?;
+        let handle = ctx
+            .primary_image_handle()
+            .map_err(|e| Error::decode(path, e))?;
+        Ok((handle.width(), handle.height()))
+    }
+
+    pub fn decode(path: &Path) -> Result<DynamicImage, Error> {
+        let ctx = context(path)?;
+        let handle = ctx
+            .primary_image_handle()
+            .map_err(|e| Error::decode(path, e))?;
+        let image = LibHeif::new()
+            .decode(&handle, ColorSpace::Rgb(RgbChroma::Rgba), None)
+            .map_err(|e| Error::decode(path, e))?;
+        let plane = image
+            .planes()
+            .interleaved
+            .ok_or_else(|| Error::decode(path, "missing interleaved plane"))?;
+        // Rows may be padded; copy them out tightly packed for `RgbaImage`.
+        let row_len = plane.width as usize * 4;
+        let mut pixels = Vec::with_capacity(row_len * plane.height as usize);
+        for row in plane.data.chunks(plane.stride).take(plane.height as usize) {
+            pixels.extend_from_slice(&row[..row_len]);
+        }
+        RgbaImage::from_raw(plane.width, plane.height, pixels)
+            .map(DynamicImage::ImageRgba8)
+            .ok_or_else(|| Error::decode(path, "truncated HEIC pixel data"))
+    }
+}
+
+#[cfg(not(feature = "heic"))]
+mod heic {
+    use std::path::Path;
+
+    use image::DynamicImage;
+
+    use crate::Error;
+
+    pub fn dimensions(_path: &Path) -> Result<(u32, u32), Error> {
+        Err(Error::CodecUnavailable("HEIC".into()))
+    }
+
+    pub fn decode(_path: &Path) -> Result<DynamicImage, Error> {
+        Err(Error::CodecUnavailable("HEIC".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io};
+
+    use float_core::test_util::scratch;
+    use image::{
+        error::{ImageFormatHint, UnsupportedError, UnsupportedErrorKind},
+        ImageFormat, Rgba, RgbaImage,
+    };
+
+    use super::*;
+
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+    const GREEN: Rgba<u8> = Rgba([0, 255, 0, 255]);
+    const BLUE: Rgba<u8> = Rgba([0, 0, 255, 255]);
+    const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+    /// 3×2, with red, green, blue and white in the top-left, top-right, bottom-left
+    /// and bottom-right corners.
+    fn corners_image() -> DynamicImage {
+        let mut img = RgbaImage::new(3, 2);
+        img.put_pixel(0, 0, RED);
+        img.put_pixel(2, 0, GREEN);
+        img.put_pixel(0, 1, BLUE);
+        img.put_pixel(2, 1, WHITE);
+        DynamicImage::ImageRgba8(img)
+    }
+
+    /// Size and top-left, top-right, bottom-left, bottom-right pixels.
+    fn corners(img: &DynamicImage) -> ((u32, u32), [Rgba<u8>; 4]) {
+        let img = img.to_rgba8();
+        let (w, h) = img.dimensions();
+        let px = |x, y| *img.get_pixel(x, y);
+        (
+            (w, h),
+            [px(0, 0), px(w - 1, 0), px(0, h - 1), px(w - 1, h - 1)],
+        )
+    }
+
+    /// `img` as a JPEG carrying an EXIF APP1 segment with `orientation`.
+    fn jpeg_with_orientation(img: &DynamicImage, orientation: u16) -> Vec<u8> {
+        let mut jpeg = Cursor::new(Vec::new());
+        img.to_rgb8()
+            .write_to(&mut jpeg, ImageOutputFormat::Jpeg(90))
+            .unwrap();
+        let jpeg = jpeg.into_inner();
+        // Big-endian TIFF header, then one IFD holding only the orientation tag.
+        let mut app1 = b"Exif\0\0MM\0\x2a\0\0\0\x08\0\x01".to_vec();
+        app1.extend_from_slice(&[0x01, 0x12, 0, 3, 0, 0, 0, 1]);
+        app1.extend_from_slice(&orientation.to_be_bytes());
+        app1.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+        let mut out = jpeg[..2].to_vec(); // SOI
+        out.extend_from_slice(&[0xff, 0xe1]);
+        out.extend_from_slice(&(app1.len() as u16 + 2).to_be_bytes());
+        out.extend_from_slice(&app1);
+        out.extend_from_slice(&jpeg[2..]);
+        out
+    }
+
+    #[test]
+    fn orientations_turn_and_mirror_the_pixels() {
+        let cases = [
+            (1, (3, 2), [RED, GREEN, BLUE, WHITE]),
+            (2, (3, 2), [GREEN, RED, WHITE, BLUE]),
+            (3, (3, 2), [WHITE, BLUE, GREEN, RED]),
+            (4, (3, 2), [BLUE, WHITE, RED, GREEN]),
+            (5, (2, 3), [RED, BLUE, GREEN, WHITE]),
+            (6, (2, 3), [BLUE, RED, WHITE, GREEN]),
+            (7, (2, 3), [WHITE, GREEN, BLUE, RED]),
+            (8, (2, 3), [GREEN, WHITE, RED, BLUE]),
+        ];
+        for (orientation, size, expected) in cases {
+            let out = apply_orientation(corners_image(), orientation);
+            assert_eq!(corners(&out), (size, expected), "orientation {orientation}");
+            assert_eq!(swaps_axes(orientation), size == (2, 3));
+        }
+        assert_eq!(corners(&apply_orientation(corners_image(), 0)).0, (3, 2));
+        assert!(!swaps_axes(9));
+    }
+
+    #[test]
+    fn missing_decoders_are_codec_errors() {
+        let unsupported = ImageError::Unsupported(UnsupportedError::from_format_and_kind(
+            ImageFormatHint::Unknown,
+            UnsupportedErrorKind::GenericFeature("avif".into()),
+        ));
+        match image_error(Path::new("/refs/shot.avif"), unsupported) {
+            Error::CodecUnavailable(name) => assert_eq!(name, "AVIF"),
+            other => panic!("unexpected {other:?}"),
+        }
+        let missing = ImageError::IoError(io::Error::from(io::ErrorKind::NotFound));
+        assert!(matches!(
+            image_error(Path::new("/refs/gone.png"), missing),
+            Error::NotFound(_)
+        ));
+    }
+
+    #[test]
+    fn web_native_formats() {
+        assert!(is_web_native(Path::new("/refs/a.PNG")));
+        assert!(is_web_native(Path::new("/refs/a.jpeg")));
+        assert!(is_web_native(Path::new("/refs/a.bmp")));
+        assert!(!is_web_native(Path::new("/refs/a.tiff")));
+        assert!(!is_web_native(Path::new("/refs/a.heic")));
+        assert!(!is_web_native(Path::new("/refs/a")));
+    }
+
+    #[test]
+    fn transcoding_produces_png() {
+        let dir = scratch("decode", "transcode");
+        for name in ["shot.bmp", "shot.tiff"] {
+            let path = dir.join(name);
+            corners_image().to_rgb8().save(&path).unwrap();
+            let png = transcode_to_png(&path).unwrap();
+            assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"), "{name}");
+            let decoded = image::load_from_memory_with_format(&png, ImageFormat::Png).unwrap();
+            assert_eq!(corners(&decoded), corners(&corners_image()), "{name}");
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rotated_jpegs_report_displayed_dimensions() {
+        let dir = scratch("decode", "exif");
+        let upright = dir.join("upright.jpg");
+        fs::write(&upright, jpeg_with_orientation(&corners_image(), 1)).unwrap();
+        assert_eq!(orientation(&upright), 1);
+        assert_eq!(image_dimensions(&upright).unwrap(), (3, 2));
+
+        let turned = dir.join("turned.jpg");
+        fs::write(&turned, jpeg_with_orientation(&corners_image(), 6)).unwrap();
+        assert_eq!(orientation(&turned), 6);
+        assert_eq!(image_dimensions(&turned).unwrap(), (2, 3));
+        assert_eq!(corners(&decode(&turned).unwrap()).0, (2, 3));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,143 @@
+//! Errors and how they reach the user. Commands return [`Error`], which the frontend
+//! receives as `{ code, message }`. Failures outside a command, such as saving settings,
+//! resizing a window or reading an image, go to the affected window as a `float-error`
+//! event with the same fields, and the HUD shows them as a toast.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+use tauri::{AppHandle, Emitter, WebviewWindow};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("no config dir available")]
+    NoConfigDir,
+    #[error("no cache dir available")]
+    NoCacheDir,
+    #[error("clipboard: {0}")]
+    Clipboard(String),
+    #[error("io: {0}")]
+    Io(#[from] io::Error),
+    #[error("serde: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("tauri: {0}")]
+    Tauri(#[from] tauri::Error),
+    #[error("image: {0}")]
+    Image(#[from] image::ImageError),
+    #[error("{} not found", .0.display())]
+    NotFound(PathBuf),
+    #[error("permission denied: {}", .0.display())]
+    PermissionDenied(PathBuf),
+    #[error("can't decode {}: {message}", path.display())]
+    Decode { path: PathBuf, message: String },
+    #[error("{0} support is not available in this build")]
+    CodecUnavailable(String),
+    #[error("settings: {0}")]
+    SettingsShape(String),
+    #[error("settings version {0} is newer than this build")]
+    SettingsTooNew(u32),
+    #[error("{0}")]
+    InvalidSettings(String),
+    #[error("can't open: {0}")]
+    Opener(String),
+    #[error("no window to act on")]
+    NoWindow,
+    #[error("no image in this window")]
+    NoImage,
+    #[error("app state unavailable")]
+    NoState,
+}
+
+impl Error {
+    /// `err` from reading or writing `path`, with missing files and denied access told
+    /// apart from other I/O failures.
+    pub fn io(path: &Path, err: io::Error) -> Error {
+        match err.kind() {
+            io::ErrorKind::NotFound => Error::NotFound(path.to_path_buf()),
+            io::ErrorKind::PermissionDenied => Error::PermissionDenied(path.to_path_buf()),
+            _ => Error::Io(err),
+        }
+    }
+
+    pub fn decode(path: &Path, message: impl ToString) -> Error {
+        Error::Decode {
+            path: path.to_path_buf(),
+            message: message.to_string(),
+        }
+    }
+
+    /// Stable identifier the frontend can match on.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::NoConfigDir | Error::NoCacheDir => "no_directory",
+            Error::Clipboard(_) => "clipboard",
+            Error::Io(_) => "io",
+            Error::Serde(_) => "serialization",
+            Error::Tauri(_) => "window",
+            Error::Image(_) => "image",
+            Error::NotFound(_) => "not_found",
+            Error::PermissionDenied(_) => "permission_denied",
+            Error::Decode { .. } => "decode",
+            Error::CodecUnavailable(_) => "unsupported_format",
+            Error::SettingsShape(_) | Error::SettingsTooNew(_) => "settings_unreadable",
+            Error::InvalidSettings(_) => "invalid_settings",
+            Error::Opener(_) => "open_failed",
+            Error::NoWindow => "no_window",
+            Error::NoImage => "no_image",
+            Error::NoState => "not_ready",
+        }
+    }
+}
+
+impl From<float_core::Error> for Error {
+    fn from(err: float_core::Error) -> Self {
+        match err {
+            float_core::Error::Io { path, source } => Error::io(&path, source),
+            float_core::Error::Serde(err) => Error::Serde(err),
+            float_core::Error::SettingsShape(message) => Error::SettingsShape(message),
+            float_core::Error::SettingsTooNew(version) => Error::SettingsTooNew(version),
+        }
+    }
+}
+
+impl Serialize for Error {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("Error", 2)?;
+        s.serialize_field("code", self.code())?;
+        s.serialize_field("message", &self.to_string())?;
+        s.end()
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+struct ErrorPayload {
+    label: String,
+    code: &'static str,
+    message: String,
+    path: Option<String>, // image the error is about, shown in its placeholder
+}
+
+/// Logs `err` and shows it in `window`'s HUD.
+pub fn report(window: &WebviewWindow, path: Option<&str>, err: &Error) {
+    log::error!(target: "error", "label={} code={} {err}", window.label(), err.code());
+    let payload = ErrorPayload {
+        label: window.label().to_string(),
+        code: err.code(),
+        message: err.to_string(),
+        path: path.map(str::to_string),
+    };
+    if let Err(emit_err) = window.emit("float-error", payload) {
+        log::warn!(target: "error", "label={} can't show error: {emit_err}", window.label());
+    }
+}
+
+/// Reports `err` in the last focused window, or only logs it when none is open.
+pub fn report_app(app: &AppHandle, err: &Error) {
+    match crate::last_focused_webview(app) {
+        Some(window) => report(&window, None, err),
+        None => log::error!(target: "error", "code={} {err}", err.code()),
+    }
+}
@@ -0,0 +1,176 @@
+//! System-wide shortcuts. They work while Float is in the background, which is the only
+//! way back out of click-through: a ghost window can't be clicked or focused.
+//!
+//! Each action has a default accelerator; `global_shortcuts` in the settings overrides
+//! it, and an empty string turns the action off.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+};
+
+use float_core::settings::HotkeyAction;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::AppState;
+
+/// Performs `action`; shortcuts call it on the main thread.
+fn run(action: HotkeyAction, app: &AppHandle) {
+    match action {
+        HotkeyAction::ShowHideAll => crate::toggle_all_windows_visible(app),
+        HotkeyAction::ClickThrough => crate::toggle_click_through(app),
+        HotkeyAction::NextImage | HotkeyAction::PreviousImage => {
+            let delta = if action == HotkeyAction::NextImage {
+                1
+            } else {
+                -1
+            };
+            if let Some(win) = crate::last_focused_webview(app) {
+                let _ = crate::navigate_selection(app, &win, delta);
+            }
+        }
+        HotkeyAction::AlwaysOnTop => crate::toggle_always_on_top(app),
+    }
+}
+
+/// Outcome of registering one action, reported by `get_global_shortcuts`.
+#[derive(Clone, Debug, Serialize)]
+pub struct HotkeyStatus {
+    pub action: HotkeyAction,
+    pub accelerator: Option<String>, // None when turned off
+    pub default: &'static str,
+    pub registered: bool,
+    pub error: Option<String>,
+}
+
+/// Name of `action` in messages, as the Shortcuts settings show it.
+pub fn label(action: HotkeyAction) -> &'static str {
+    match action {
+        HotkeyAction::ShowHideAll => "Show/Hide All Windows",
+        HotkeyAction::ClickThrough => "Toggle Click-Through",
+        HotkeyAction::NextImage => "Next Image",
+        HotkeyAction::PreviousImage => "Previous Image",
+        HotkeyAction::AlwaysOnTop => "Toggle Always on Top",
+    }
+}
+
+/// The accelerator each action uses after applying `overrides`.
+pub fn effective(
+    overrides: &BTreeMap<HotkeyAction, String>,
+) -> Vec<(HotkeyAction, Option<String>)> {
+    HotkeyAction::ALL
+        .iter()
+        .map(|action| {
+            let accelerator = match overrides.get(action) {
+                Some(value) => value.trim().to_string(),
+                None => action.default_accelerator().to_string(),
+            };
+            (*action, Some(accelerator).filter(|a| !a.is_empty()))
+        })
+        .collect()
+}
+
+/// (Re)registers every action from the current settings and records the outcome. An
+/// accelerator that doesn't parse, is used twice, or is held by another app is skipped
+/// and logged; the others still work.
+pub fn register(app: &AppHandle) {
+    let state = match app.try_state::<AppState>() {
+        Some(state) => state,
+        None => return,
+    };
+    let overrides = state.settings.lock().global_shortcuts.clone();
+    let shortcuts = app.global_shortcut();
+    let _ = shortcuts.unregister_all();
+
+    let mut taken: HashMap<u32, HotkeyAction> = HashMap::new();
+    let mut statuses = Vec::new();
+    for (action, accelerator) in effective(&overrides) {
+        let mut status = HotkeyStatus {
+            action,
+            accelerator: accelerator.clone(),
+            default: action.default_accelerator(),
+            registered: false,
+            error: None,
+        };
+        if let Some(accelerator) = accelerator {
+            let result = Shortcut::from_str(&accelerator)
+                .map_err(|err| format!("invalid shortcut: {err}"))
+                .and_then(|shortcut| match taken.get(&shortcut.id()) {
+                    Some(other) => Err(format!("also assigned to {other:?}")),
+                    None => Ok(shortcut),
+                })
+                .and_then(|shortcut| {
+                    shortcuts
+                        .on_shortcut(shortcut, move |app, _shortcut, event| {
+                            if event.state() != ShortcutState::Pressed {
+                                return;
+                            }
+                            let handle = app.clone();
+                            let _ = app.run_on_main_thread(move || run(action, &handle));
+                        })
+                        .map_err(|err| format!("in use by another application ({err})"))?;
+                    taken.insert(shortcut.id(), action);
+                    Ok(())
+                });
+            match result {
+                Ok(()) => status.registered = true,
+                Err(err) => {
+                    log::warn!("global shortcut {accelerator} for {action:?} unavailable: {err}");
+                    status.error = Some(err);
+                }
+            }
+        }
+        statuses.push(status);
+    }
+    *state.hotkey_status.lock() = statuses;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accelerator_of(
+        overrides: &BTreeMap<HotkeyAction, String>,
+        action: HotkeyAction,
+    ) -> Option<String> {
+        effective(overrides)
+            .into_iter()
+            .find(|(a, _)| *a == action)
+            .and_then(|(_, accelerator)| accelerator)
+    }
+
+    #[test]
+    fn every_action_starts_from_its_default() {
+        let all = effective(&BTreeMap::new());
+        assert_eq!(all.len(), HotkeyAction::ALL.len());
+        for (action, accelerator) in all {
+            assert_eq!(accelerator.as_deref(), Some(action.default_accelerator()));
+        }
+    }
+
+    #[test]
+    fn overrides_replace_one_action() {
+        let overrides = BTreeMap::from([(HotkeyAction::NextImage, " Alt+Shift+N ".to_string())]);
+        assert_eq!(
+            accelerator_of(&overrides, HotkeyAction::NextImage).as_deref(),
+            Some("Alt+Shift+N")
+        );
+        assert_eq!(
+            accelerator_of(&overrides, HotkeyAction::PreviousImage).as_deref(),
+            Some(HotkeyAction::PreviousImage.default_accelerator())
+        );
+    }
+
+    #[test]
+    fn empty_overrides_turn_an_action_off() {
+        let overrides = BTreeMap::from([
+            (HotkeyAction::ShowHideAll, String::new()),
+            (HotkeyAction::AlwaysOnTop, "   ".to_string()),
+        ]);
+        assert_eq!(accelerator_of(&overrides, HotkeyAction::ShowHideAll), None);
+        assert_eq!(accelerator_of(&overrides, HotkeyAction::AlwaysOnTop), None);
+        assert!(accelerator_of(&overrides, HotkeyAction::ClickThrough).is_some());
+    }
+}
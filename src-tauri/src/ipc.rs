@@ -0,0 +1,434 @@
+//! Local socket (Unix domain socket, named pipe on Windows) owned by the running
+//! instance. A second launch hands its arguments over it and exits, so only one
+//! process ever writes `settings.json`; `float ctl` and editor plugins drive the
+//! app through it too.
+//!
+//! The protocol is one JSON object per line in each direction, e.g.
+//! `{"cmd":"next","window":"main"}` answered by `{"ok":true,"data":{...}}`.
+
+use std::{
+    fs::{File, OpenOptions, TryLockError},
+    io::{self, BufRead, BufReader, Write},
+    path::PathBuf,
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use interprocess::local_socket::{prelude::*, Listener, ListenerOptions, Name, Stream};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager, WebviewWindow};
+
+use float_core::{fit::FitMode, transform::TransformAction};
+
+use crate::{cli::Cli, AppState};
+
+/// Debug builds use their own socket so `tauri dev` doesn't forward to an installed Float.
+const SOCKET_NAME: &str = if cfg!(debug_assertions) {
+    "float-dev.sock"
+} else {
+    "float.sock"
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum Request {
+    /// Arguments of a second launch, opened in new windows.
+    Launch {
+        args: Cli,
+    },
+    /// Opens paths in `window` (default: last focused) or in a new window. Paths must
+    /// be absolute: the app doesn't know the client's working directory.
+    Open {
+        paths: Vec<PathBuf>,
+        window: Option<String>,
+        #[serde(default)]
+        new_window: bool,
+    },
+    Next {
+        window: Option<String>,
+    },
+    Previous {
+        window: Option<String>,
+    },
+    /// Fits the window with `mode`, or the chosen fit mode when absent.
+    Fit {
+        window: Option<String>,
+        #[serde(default)]
+        mode: Option<FitMode>,
+    },
+    /// Sets the window's aspect lock, or flips it when `enabled` is absent.
+    AspectLock {
+        window: Option<String>,
+        enabled: Option<bool>,
+    },
+    Opacity {
+        window: Option<String>,
+        percent: u8,
+    },
+    /// Rotates or flips the window's image.
+    Transform {
+        window: Option<String>,
+        action: TransformAction,
+    },
+    Close {
+        window: Option<String>,
+    },
+    List,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl Response {
+    pub fn ok(data: Option<Value>) -> Self {
+        Self {
+            ok: true,
+            data,
+            ..Self::default()
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            error: Some(message.into()),
+            ..Self::default()
+        }
+    }
+}
+
+/// What `list` reports per window, and what window commands reply with.
+#[derive(Debug, Serialize)]
+struct WindowInfo {
+    label: String,
+    path: Option<String>,
+    index: Option<usize>,
+    total: usize,
+    focused: bool,
+}
+
+/// Where the instance lock lives, and on Unix the socket file next to it: the per-user
+/// runtime dir (`$XDG_RUNTIME_DIR`), or the app cache dir where there is none (macOS,
+/// Windows).
+fn runtime_dir() -> io::Result<PathBuf> {
+    let base = directories::BaseDirs::new()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no home directory"))?;
+    let dir = match base.runtime_dir() {
+        Some(dir) => dir.to_path_buf(),
+        None => base.cache_dir().join(crate::APP_IDENTIFIER),
+    };
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Pipe names are machine-wide on Windows, so scope them to the user.
+#[cfg(target_os = "windows")]
+fn socket_name() -> io::Result<Name<'static>> {
+    use interprocess::local_socket::GenericNamespaced;
+    let user = std::env::var("USERNAME").unwrap_or_default();
+    format!("{user}-{SOCKET_NAME}").to_ns_name::<GenericNamespaced>()
+}
+
+#[cfg(not(target_os = "windows"))]
+fn socket_name() -> io::Result<Name<'static>> {
+    use interprocess::local_socket::GenericFilePath;
+    runtime_dir()?
+        .join(SOCKET_NAME)
+        .to_fs_name::<GenericFilePath>()
+}
+
+/// Sends `request` to the running instance and waits for its reply. Fails to connect
+/// when no instance is running.
+pub fn send(request: &Request) -> io::Result<Response> {
+    let mut conn = BufReader::new(Stream::connect(socket_name()?)?);
+    let mut line = serde_json::to_string(request)?;
+    line.push('\n');
+    conn.get_mut().write_all(line.as_bytes())?;
+    let mut reply = String::new();
+    conn.read_line(&mut reply)?;
+    Ok(serde_json::from_str(&reply)?)
+}
+
+/// How long a launch waits for another instance that holds the lock but hasn't bound
+/// its socket yet.
+const FORWARD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The outcome of [`claim`].
+pub enum Claim {
+    /// This process is the instance; [`serve`] it once the app is up.
+    Primary(Instance),
+    /// Another instance took the request and answered with this.
+    Forwarded(Response),
+}
+
+/// The socket this process owns, and the lock that keeps other launches from taking
+/// it over. Both are released when the process exits.
+pub struct Instance {
+    listener: Listener,
+    _lock: File,
+}
+
+/// Becomes the running instance, or hands `request` to the one that already is.
+///
+/// Launches race for an exclusive lock on `float.sock.lock`. The winner binds the
+/// socket before the app is built, so a launch that loses always has someone to
+/// forward to; while holding the lock, a socket file nobody answers on can only be a
+/// leftover from a crashed instance.
+pub fn claim(request: &Request) -> io::Result<Claim> {
+    let lock = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(runtime_dir()?.join(format!("{SOCKET_NAME}.lock")))?;
+    match lock.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            let response = forward(request).unwrap_or_else(|err| {
+                Response::error(format!("the running Float isn't answering: {err}"))
+            });
+            return Ok(Claim::Forwarded(response));
+        }
+        Err(TryLockError::Error(err)) => return Err(err),
+    }
+    // Only replace a socket file once nobody answers on it.
+    if let Ok(response) = send(request) {
+        return Ok(Claim::Forwarded(response));
+    }
+    let listener = ListenerOptions::new()
+        .name(socket_name()?)
+        .try_overwrite(true)
+        .create_sync()?;
+    Ok(Claim::Primary(Instance {
+        listener,
+        _lock: lock,
+    }))
+}
+
+/// Sends `request` to the instance holding the lock, waiting for it to start listening.
+fn forward(request: &Request) -> io::Result<Response> {
+    let deadline = Instant::now() + FORWARD_TIMEOUT;
+    loop {
+        match send(request) {
+            Ok(response) => return Ok(response),
+            Err(_) if Instant::now() < deadline => thread::sleep(Duration::from_millis(50)),
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Starts accepting requests on a background thread for the lifetime of the app.
+/// Launches that connected while the app was starting are answered now.
+pub fn serve(app: AppHandle, instance: Instance) {
+    thread::spawn(move || {
+        for conn in instance.listener.incoming().filter_map(Result::ok) {
+            let app = app.clone();
+            thread::spawn(move || handle_connection(&app, conn));
+        }
+    });
+}
+
+fn handle_connection(app: &AppHandle, conn: Stream) {
+    let mut conn = BufReader::new(conn);
+    let mut line = String::new();
+    while matches!(conn.read_line(&mut line), Ok(n) if n > 0) {
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => dispatch(app, request),
+            Err(err) => Response::error(format!("bad request: {err}")),
+        };
+        let mut reply = serde_json::to_string(&response).unwrap_or_default();
+        reply.push('\n');
+        if conn.get_mut().write_all(reply.as_bytes()).is_err() {
+            return;
+        }
+        line.clear();
+    }
+}
+
+/// Rejects what `float ctl open` would have refused: paths relative to some other
+/// process's working directory, missing paths and files that aren't images.
+fn check_open_paths(paths: &[PathBuf]) -> Result<(), String> {
+    for path in paths {
+        if !path.is_absolute() {
+            return Err(format!("{}: paths must be absolute", path.display()));
+        }
+        crate::cli::existing_path(&path.to_string_lossy())
+            .map_err(|err| format!("{}: {err}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Runs `request` on the main thread, where windows and menus are driven from.
+fn dispatch(app: &AppHandle, request: Request) -> Response {
+    let (tx, rx) = mpsc::channel();
+    let app_for_main = app.clone();
+    let scheduled = app.run_on_main_thread(move || {
+        let _ = tx.send(handle(&app_for_main, request));
+    });
+    if scheduled.is_err() {
+        return Response::error("Float is shutting down");
+    }
+    rx.recv()
+        .unwrap_or_else(|_| Response::error("request was dropped"))
+}
+
+fn handle(app: &AppHandle, request: Request) -> Response {
+    match run(app, request) {
+        Ok(data) => Response::ok(data),
+        Err(message) => Response::error(message),
+    }
+}
+
+fn run(app: &AppHandle, request: Request) -> Result<Option<Value>, String> {
+    match request {
+        Request::Launch { args } => {
+            crate::open_forwarded_launch(app, &args);
+            Ok(None)
+        }
+        Request::Open {
+            paths,
+            window,
+            new_window,
+        } => {
+            check_open_paths(&paths)?;
+            let files = crate::expand_paths(app, &paths);
+            if files.is_empty() {
+                return Err("no images in the given paths".into());
+            }
+            let win = if new_window {
+                crate::spawn_new_window_with_files(app, files, &Default::default())
+                    .ok_or("could not open a new window")?;
+                target_window(app, None)?
+            } else {
+                let win = target_window(app, window.as_deref())?;
+                let _ = crate::apply_selection(app, &win, files);
+                win
+            };
+            info_data(app, &win)
+        }
+        Request::Next { window } => {
+            let win = target_window(app, window.as_deref())?;
+            let _ = crate::navigate_selection(app, &win, 1);
+            info_data(app, &win)
+        }
+        Request::Previous { window } => {
+            let win = target_window(app, window.as_deref())?;
+            let _ = crate::navigate_selection(app, &win, -1);
+            info_data(app, &win)
+        }
+        Request::Fit { window, mode } => {
+            let win = target_window(app, window.as_deref())?;
+            crate::fit_now(app.clone(), win.clone(), mode)
+                .inspect_err(|err| crate::error::report(&win, None, err))
+                .map_err(|e| e.to_string())?;
+            info_data(app, &win)
+        }
+        Request::AspectLock { window, enabled } => {
+            let win = target_window(app, window.as_deref())?;
+            let enabled =
+                crate::set_window_aspect_lock(app, &win, enabled).map_err(|e| e.to_string())?;
+            Ok(Some(
+                json!({ "label": win.label(), "aspect_lock": enabled }),
+            ))
+        }
+        Request::Opacity { window, percent } => {
+            let win = target_window(app, window.as_deref())?;
+            crate::choose_opacity(app, &win, percent).map_err(|e| e.to_string())?;
+            info_data(app, &win)
+        }
+        Request::Transform { window, action } => {
+            let win = target_window(app, window.as_deref())?;
+            crate::transform_image(app.clone(), win.clone(), action).map_err(|e| e.to_string())?;
+            info_data(app, &win)
+        }
+        Request::Close { window } => {
+            let win = target_window(app, window.as_deref())?;
+            win.close().map_err(|e| e.to_string())?;
+            Ok(None)
+        }
+        Request::List => {
+            let mut windows: Vec<WindowInfo> = app
+                .webview_windows()
+                .values()
+                .map(|win| window_info(app, win))
+                .collect();
+            windows.sort_by(|a, b| float_core::selection::natural_cmp(&a.label, &b.label));
+            serde_json::to_value(windows)
+                .map(Some)
+                .map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// The window named `label`, or the last focused one.
+fn target_window(app: &AppHandle, label: Option<&str>) -> Result<WebviewWindow, String> {
+    match label {
+        Some(label) => app
+            .get_webview_window(label)
+            .ok_or_else(|| format!("no window named {label:?}")),
+        None => crate::last_focused_webview(app).ok_or_else(|| "no open windows".into()),
+    }
+}
+
+fn window_info(app: &AppHandle, window: &WebviewWindow) -> WindowInfo {
+    let label = window.label().to_string();
+    let selection = app
+        .try_state::<AppState>()
+        .and_then(|state| state.selections.lock().get(&label).cloned());
+    let last_focused = app
+        .try_state::<AppState>()
+        .and_then(|state| state.last_focused_window.lock().clone());
+    WindowInfo {
+        path: selection
+            .as_ref()
+            .and_then(|sel| sel.files.get(sel.active).cloned()),
+        index: selection.as_ref().map(|sel| sel.active),
+        total: selection.map(|sel| sel.files.len()).unwrap_or(0),
+        focused: last_focused.as_deref() == Some(label.as_str()),
+        label,
+    }
+}
+
+fn info_data(app: &AppHandle, window: &WebviewWindow) -> Result<Option<Value>, String> {
+    serde_json::to_value(window_info(app, window))
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use float_core::test_util::scratch;
+
+    use super::*;
+
+    #[test]
+    fn open_takes_only_absolute_existing_images() {
+        let dir = scratch("ipc", "open");
+        fs::write(dir.join("shot.png"), b"").unwrap();
+        fs::write(dir.join("notes.txt"), b"").unwrap();
+        assert_eq!(
+            check_open_paths(&[dir.join("shot.png"), dir.clone()]),
+            Ok(())
+        );
+        assert_eq!(check_open_paths(&[]), Ok(()));
+        assert!(check_open_paths(&[PathBuf::from("shot.png")])
+            .unwrap_err()
+            .ends_with("paths must be absolute"));
+        assert!(check_open_paths(&[dir.join("gone.png")])
+            .unwrap_err()
+            .ends_with("no such file or folder"));
+        assert!(check_open_paths(&[dir.join("notes.txt")])
+            .unwrap_err()
+            .contains("not a supported image"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,372 @@
+//! Menu keyboard shortcuts. Each menu command has a platform default, and the
+//! `keybindings` table in the settings overrides it by menu id (an empty string removes
+//! the shortcut).
+
+use std::collections::{BTreeMap, HashMap};
+
+use float_core::settings::HotkeyAction;
+use muda::accelerator::Accelerator;
+use serde::Serialize;
+use tauri::{
+    menu::{MenuItem, MenuItemBuilder},
+    AppHandle, Manager, Wry,
+};
+
+use crate::{hotkeys, AppState};
+
+struct Binding {
+    id: &'static str,
+    label: &'static str,
+    macos: &'static str,
+    other: &'static str,
+}
+
+const BINDINGS: &[Binding] = &[
+    Binding {
+        id: "new_window",
+        label: "New Window…",
+        macos: "Cmd+T",
+        other: "Ctrl+T",
+    },
+    Binding {
+        id: "open",
+        label: "Open…",
+        macos: "Cmd+O",
+        other: "Ctrl+O",
+    },
+    Binding {
+        id: "open_folder",
+        label: "Open Folder…",
+        macos: "Cmd+Shift+O",
+        other: "Ctrl+Shift+O",
+    },
+    Binding {
+        id: "save_as",
+        label: "Save As…",
+        macos: "Cmd+Shift+S",
+        other: "Ctrl+Shift+S",
+    },
+    Binding {
+        id: "close_window",
+        label: "Close Window",
+        macos: "Cmd+W",
+        other: "Ctrl+W",
+    },
+    Binding {
+        id: "settings",
+        label: "Settings…",
+        macos: "Cmd+,",
+        other: "Ctrl+,",
+    },
+    Binding {
+        id: "reset_cache",
+        label: "Reset Cache",
+        macos: "Cmd+Shift+Backspace",
+        other: "Ctrl+Shift+Backspace",
+    },
+    Binding {
+        id: "quit",
+        label: "Quit",
+        macos: "Cmd+Q",
+        other: "Ctrl+Q",
+    },
+    // No default: Cmd/Ctrl+V stays the webview's own paste, which pastes an image when
+    // no text field has focus.
+    Binding {
+        id: "paste_image",
+        label: "Paste Image",
+        macos: "",
+        other: "",
+    },
+    Binding {
+        id: "fit_now",
+        label: "Fit to Image Now",
+        macos: "Cmd+F",
+        other: "Ctrl+F",
+    },
+    Binding {
+        id: "previous_file",
+        label: "Previous File",
+        macos: "Alt+Cmd+[",
+        other: "Ctrl+[",
+    },
+    Binding {
+        id: "next_file",
+        label: "Next File",
+        macos: "Alt+Cmd+]",
+        other: "Ctrl+]",
+    },
+    Binding {
+        id: "zoom_in",
+        label: "Zoom In",
+        macos: "Cmd+=",
+        other: "Ctrl+=",
+    },
+    Binding {
+        id: "zoom_out",
+        label: "Zoom Out",
+        macos: "Cmd+-",
+        other: "Ctrl+-",
+    },
+    Binding {
+        id: "zoom_actual",
+        label: "Actual Pixels",
+        macos: "Cmd+0",
+        other: "Ctrl+0",
+    },
+    Binding {
+        id: "zoom_fit",
+        label: "Zoom to Fit",
+        macos: "Cmd+9",
+        other: "Ctrl+9",
+    },
+    Binding {
+        id: "rotate_cw",
+        label: "Rotate Right",
+        macos: "Cmd+R",
+        other: "Ctrl+R",
+    },
+    Binding {
+        id: "rotate_ccw",
+        label: "Rotate Left",
+        macos: "Cmd+L",
+        other: "Ctrl+L",
+    },
+    Binding {
+        id: "flip_horizontal",
+        label: "Flip Horizontal",
+        macos: "Cmd+Shift+H",
+        other: "Ctrl+Shift+H",
+    },
+    Binding {
+        id: "flip_vertical",
+        label: "Flip Vertical",
+        macos: "Cmd+Shift+V",
+        other: "Ctrl+Shift+V",
+    },
+    Binding {
+        id: "opacity_up",
+        label: "Increase Opacity",
+        macos: "Alt+Cmd+=",
+        other: "Ctrl+Alt+=",
+    },
+    Binding {
+        id: "opacity_down",
+        label: "Decrease Opacity",
+        macos: "Alt+Cmd+-",
+        other: "Ctrl+Alt+-",
+    },
+    Binding {
+        id: "opacity_reset",
+        label: "Fully Opaque",
+        macos: "Alt+Cmd+0",
+        other: "Ctrl+Alt+0",
+    },
+];
+
+impl Binding {
+    fn default_accelerator(&self) -> &'static str {
+        if cfg!(target_os = "macos") {
+            self.macos
+        } else {
+            self.other
+        }
+    }
+}
+
+fn binding(id: &str) -> Option<&'static Binding> {
+    BINDINGS.iter().find(|b| b.id == id)
+}
+
+/// One row of the Shortcuts section.
+#[derive(Clone, Debug, Serialize)]
+pub struct KeybindingInfo {
+    pub command: &'static str,
+    pub label: &'static str,
+    pub accelerator: Option<String>, // None when removed
+    pub default: &'static str,
+}
+
+fn effective(overrides: &BTreeMap<String, String>, binding: &Binding) -> Option<String> {
+    let accelerator = match overrides.get(binding.id) {
+        Some(value) => value.trim(),
+        None => binding.default_accelerator(),
+    };
+    Some(accelerator.to_string()).filter(|a| !a.is_empty())
+}
+
+/// Every command with the shortcut it currently uses.
+pub fn table(overrides: &BTreeMap<String, String>) -> Vec<KeybindingInfo> {
+    BINDINGS
+        .iter()
+        .map(|b| KeybindingInfo {
+            command: b.id,
+            label: b.label,
+            accelerator: effective(overrides, b),
+            default: b.default_accelerator(),
+        })
+        .collect()
+}
+
+/// Checks `overrides` for unknown commands, accelerators that don't parse and shortcuts
+/// used by two commands or by one of the `global` shortcuts, returning one message per
+/// problem.
+pub fn validate(
+    overrides: &BTreeMap<String, String>,
+    global: &BTreeMap<HotkeyAction, String>,
+) -> Result<(), Vec<String>> {
+    let mut problems = Vec::new();
+    for id in overrides.keys() {
+        if binding(id).is_none() {
+            problems.push(format!("{id}: unknown command"));
+        }
+    }
+    // Global shortcuts are checked among themselves when they are registered; here they
+    // only claim their keys.
+    let mut taken: HashMap<Accelerator, String> = HashMap::new();
+    for (action, accelerator) in hotkeys::effective(global) {
+        if let Some(parsed) = accelerator.and_then(|a| a.parse::<Accelerator>().ok()) {
+            taken
+                .entry(parsed)
+                .or_insert_with(|| format!("the global shortcut {}", hotkeys::label(action)));
+        }
+    }
+    for b in BINDINGS {
+        let accelerator = match effective(overrides, b) {
+            Some(accelerator) => accelerator,
+            None => continue,
+        };
+        match accelerator.parse::<Accelerator>() {
+            Ok(parsed) => match taken.get(&parsed) {
+                Some(other) => problems.push(format!(
+                    "{}: {accelerator} is already used by {other}",
+                    b.label
+                )),
+                None => {
+                    taken.insert(parsed, b.label.to_string());
+                }
+            },
+            Err(err) => problems.push(format!("{}: {accelerator} is not valid ({err})", b.label)),
+        }
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
+    }
+}
+
+/// Builds the menu item for `id` with its label and current shortcut, and keeps it so
+/// [`apply`] can update the shortcut later.
+pub fn menu_item(
+    app: &AppHandle,
+    overrides: &BTreeMap<String, String>,
+    id: &'static str,
+) -> tauri::Result<MenuItem<Wry>> {
+    let b = binding(id).expect("menu command has a binding");
+    let mut builder = MenuItemBuilder::with_id(id, b.label);
+    if let Some(accelerator) = effective(overrides, b) {
+        builder = builder.accelerator(accelerator);
+    }
+    let item = builder.build(app)?;
+    if let Some(state) = app.try_state::<AppState>() {
+        state.menu_items.lock().insert(id, item.clone());
+    }
+    Ok(item)
+}
+
+/// Puts the shortcuts from `overrides` on the existing menu items.
+pub fn apply(app: &AppHandle, overrides: &BTreeMap<String, String>) {
+    if let Some(state) = app.try_state::<AppState>() {
+        for (id, item) in state.menu_items.lock().iter() {
+            if let Some(b) = binding(id) {
+                let _ = item.set_accelerator(effective(overrides, b));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table_of(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(id, accelerator)| (id.to_string(), accelerator.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        assert_eq!(validate(&BTreeMap::new(), &BTreeMap::new()), Ok(()));
+    }
+
+    #[test]
+    fn every_command_has_its_own_id() {
+        let mut ids: Vec<_> = BINDINGS.iter().map(|b| b.id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), BINDINGS.len());
+    }
+
+    #[test]
+    fn paste_leaves_the_webview_paste_alone() {
+        let paste = table(&BTreeMap::new())
+            .into_iter()
+            .find(|info| info.command == "paste_image")
+            .unwrap();
+        assert_eq!(paste.accelerator, None);
+    }
+
+    #[test]
+    fn overrides_replace_and_remove_defaults() {
+        let overrides = table_of(&[("open", " Ctrl+Shift+P "), ("quit", "")]);
+        let rows = table(&overrides);
+        let row = |id: &str| rows.iter().find(|info| info.command == id).unwrap();
+        assert_eq!(row("open").accelerator.as_deref(), Some("Ctrl+Shift+P"));
+        assert_eq!(row("quit").accelerator, None);
+        assert!(row("close_window").accelerator.is_some());
+    }
+
+    #[test]
+    fn unknown_commands_and_bad_accelerators_are_reported() {
+        let overrides = table_of(&[("teleport", "Ctrl+K"), ("open", "Ctrl+Nope")]);
+        let problems = validate(&overrides, &BTreeMap::new()).unwrap_err();
+        assert_eq!(problems.len(), 2, "{problems:?}");
+        assert_eq!(problems[0], "teleport: unknown command");
+        assert!(problems[1].starts_with("Open…: Ctrl+Nope is not valid"));
+    }
+
+    #[test]
+    fn two_commands_cant_share_a_shortcut() {
+        let taken = binding("close_window").unwrap().default_accelerator();
+        let overrides = table_of(&[("open", taken)]);
+        let problems = validate(&overrides, &BTreeMap::new()).unwrap_err();
+        assert_eq!(problems.len(), 1, "{problems:?}");
+        assert!(problems[0].contains("already used by"));
+        // Freeing the other command's shortcut resolves it.
+        let overrides = table_of(&[("open", taken), ("close_window", "")]);
+        assert_eq!(validate(&overrides, &BTreeMap::new()), Ok(()));
+    }
+
+    #[test]
+    fn menu_shortcuts_cant_take_a_global_one() {
+        let global = HotkeyAction::AlwaysOnTop.default_accelerator();
+        let overrides = table_of(&[("open", global)]);
+        let problems = validate(&overrides, &BTreeMap::new()).unwrap_err();
+        assert_eq!(
+            problems,
+            [format!(
+                "Open…: {global} is already used by the global shortcut Toggle Always on Top"
+            )]
+        );
+        // Turning the global shortcut off frees its keys.
+        let off = BTreeMap::from([(HotkeyAction::AlwaysOnTop, String::new())]);
+        assert_eq!(validate(&overrides, &off), Ok(()));
+        // And a global shortcut moved onto a menu command's keys clashes too.
+        let taken = binding("open").unwrap().default_accelerator();
+        let moved = BTreeMap::from([(HotkeyAction::NextImage, taken.to_string())]);
+        assert!(validate(&BTreeMap::new(), &moved).is_err());
+    }
+}
@@ -0,0 +1,132 @@
+//! Leveled logs for window lifecycle, selection changes, fit computations and settings
+//! persistence. They go to stderr and to `float.log` in the app log directory, which
+//! Help → Reveal Logs opens; release builds on Windows have no console to show stderr.
+//!
+//! Each area logs under its own target (`window`, `selection`, `fit`, `settings`, `error`)
+//! with `key=value` fields, so `FLOAT_LOG=info,fit=debug` turns up one area alone.
+
+use std::path::PathBuf;
+
+use log::LevelFilter;
+use tauri::{plugin::TauriPlugin, AppHandle, Manager, Runtime};
+use tauri_plugin_log::{RotationStrategy, Target, TargetKind, TimezoneStrategy};
+
+use crate::Error;
+
+/// Overrides the level, e.g. `debug` or `warn,settings=debug`.
+pub const ENV_VAR: &str = "FLOAT_LOG";
+const FILE_NAME: &str = "float";
+const MAX_FILE_SIZE: u128 = 1_000_000; // bytes before the file is rotated
+const KEEP_FILES: usize = 5;
+
+/// The log plugin, plus the parts of [`ENV_VAR`] it couldn't parse so they can be
+/// logged once it is running.
+pub fn plugin<R: Runtime>() -> (TauriPlugin<R>, Vec<String>) {
+    let spec = std::env::var(ENV_VAR).unwrap_or_default();
+    let (level, modules, problems) = parse_filter(&spec);
+    let mut builder = tauri_plugin_log::Builder::new()
+        .clear_targets()
+        .target(Target::new(TargetKind::Stderr))
+        .target(Target::new(TargetKind::LogDir {
+            file_name: Some(FILE_NAME.into()),
+        }))
+        .max_file_size(MAX_FILE_SIZE)
+        .rotation_strategy(RotationStrategy::KeepSome(KEEP_FILES))
+        .timezone_strategy(TimezoneStrategy::UseLocal)
+        .level(level);
+    for (module, level) in modules {
+        builder = builder.level_for(module, level);
+    }
+    (builder.build(), problems)
+}
+
+/// Splits `warn,fit=debug` into a default level and per-target levels. An empty spec
+/// logs at info.
+fn parse_filter(spec: &str) -> (LevelFilter, Vec<(String, LevelFilter)>, Vec<String>) {
+    let mut level = LevelFilter::Info;
+    let mut modules = Vec::new();
+    let mut problems = Vec::new();
+    for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        match part.split_once('=') {
+            Some((module, _)) if module.trim().is_empty() => {
+                problems.push(format!("{ENV_VAR}: missing target in {part:?}"))
+            }
+            Some((module, value)) => match value.trim().parse() {
+                Ok(l) => modules.push((module.trim().to_string(), l)),
+                Err(_) => problems.push(format!("{ENV_VAR}: unknown level in {part:?}")),
+            },
+            None => match part.parse() {
+                Ok(l) => level = l,
+                Err(_) => problems.push(format!("{ENV_VAR}: unknown level {part:?}")),
+            },
+        }
+    }
+    (level, modules, problems)
+}
+
+/// The current log file.
+pub fn file_path(app: &AppHandle) -> Result<PathBuf, Error> {
+    Ok(app.path().app_log_dir()?.join(format!("{FILE_NAME}.log")))
+}
+
+/// Shows the log file in the file manager, or its folder when nothing was logged yet.
+pub fn reveal(app: &AppHandle) -> Result<(), Error> {
+    let file = file_path(app)?;
+    if file.exists() {
+        return tauri_plugin_opener::reveal_item_in_dir(&file)
+            .map_err(|err| Error::Opener(err.to_string()));
+    }
+    let dir = file.parent().map(PathBuf::from).unwrap_or(file);
+    std::fs::create_dir_all(&dir).map_err(|err| Error::io(&dir, err))?;
+    tauri_plugin_opener::open_path(&dir, None::<&str>).map_err(|err| Error::Opener(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_specs_log_at_info() {
+        for spec in ["", " ", ",,"] {
+            let (level, modules, problems) = parse_filter(spec);
+            assert_eq!(level, LevelFilter::Info);
+            assert!(modules.is_empty() && problems.is_empty(), "{spec:?}");
+        }
+    }
+
+    #[test]
+    fn levels_apply_globally_and_per_target() {
+        let (level, modules, problems) = parse_filter("warn, fit = DEBUG ,settings=trace");
+        assert_eq!(level, LevelFilter::Warn);
+        assert_eq!(
+            modules,
+            [
+                ("fit".to_string(), LevelFilter::Debug),
+                ("settings".to_string(), LevelFilter::Trace)
+            ]
+        );
+        assert!(problems.is_empty());
+        assert_eq!(parse_filter("off").0, LevelFilter::Off);
+    }
+
+    #[test]
+    fn the_last_global_level_wins() {
+        assert_eq!(parse_filter("debug,error").0, LevelFilter::Error);
+    }
+
+    #[test]
+    fn malformed_parts_are_reported_and_skipped() {
+        let (level, modules, problems) = parse_filter("loud,fit=chatty,=debug,window=,debug");
+        assert_eq!(level, LevelFilter::Debug);
+        assert!(modules.is_empty());
+        assert_eq!(
+            problems,
+            [
+                "FLOAT_LOG: unknown level \"loud\"",
+                "FLOAT_LOG: unknown level in \"fit=chatty\"",
+                "FLOAT_LOG: missing target in \"=debug\"",
+                "FLOAT_LOG: unknown level in \"window=\"",
+            ]
+        );
+    }
+}
@@ -1,64 +1,67 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
-use base64::{engine::general_purpose, Engine};
+mod cli;
+mod clipboard;
+mod ctl;
+mod decode;
+mod error;
+mod hotkeys;
+mod ipc;
+mod keybindings;
+mod logging;
+mod modifiers;
+mod opacity;
+mod placement;
+mod protocol;
+mod session;
+mod watcher;
+
 use directories::{BaseDirs, ProjectDirs};
+use error::Error;
+use float_core::{
+    aspect, fit, migrate,
+    selection::{self, is_image_path, SelectionState},
+    settings::{HotkeyAction, PersistedState, ViewState, WindowSizeUnits},
+    store::{self, SettingsStore},
+    transform,
+};
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     fs,
-    path::PathBuf,
-    sync::atomic::AtomicUsize,
-    time::Duration,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, OnceLock,
+    },
+    time::{Duration, UNIX_EPOCH},
 };
 use tauri::menu::{
-    CheckMenuItem, CheckMenuItemBuilder, MenuBuilder, MenuItemBuilder, SubmenuBuilder,
+    CheckMenuItem, CheckMenuItemBuilder, MenuBuilder, MenuItem, MenuItemBuilder, SubmenuBuilder,
 };
 use tauri::{
-    async_runtime, AppHandle, Emitter, Manager, WebviewUrl, WebviewWindow, WindowEvent, Wry,
+    async_runtime, AppHandle, DragDropEvent, Emitter, Manager, WebviewUrl, WebviewWindow,
+    WindowEvent, Wry,
 };
 use tauri_plugin_dialog::DialogExt;
 use tokio::time::sleep;
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(rename_all = "lowercase")]
-enum WindowSizeUnits {
-    Logical,
-    Physical,
-}
-
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
-struct PersistedState {
-    last_file: Option<String>,
-    aspect_lock: bool,
-    window_w: Option<f64>,
-    window_h: Option<f64>,
-    window_size_units: Option<WindowSizeUnits>,
-}
-
 #[derive(Clone, Debug, Serialize)]
 struct ActiveFilePayload {
     path: Option<String>,
     index: Option<usize>,
     total: Option<usize>,
+    revision: Option<u64>, // cache-buster, changes whenever the file is rewritten
+    missing: bool,
+    wrap: bool,
+    transform: transform::ImageTransform,
 }
 
-#[derive(Clone, Debug)]
-struct SelectionState {
-    files: Vec<String>,
-    active: usize,
-}
-
-#[derive(thiserror::Error, Debug)]
-enum Error {
-    #[error("no config dir available")]
-    NoConfigDir,
-    #[error("io: {0}")]
-    Io(#[from] std::io::Error),
-    #[error("serde: {0}")]
-    Serde(#[from] serde_json::Error),
-    #[error("tauri: {0}")]
-    Tauri(#[from] tauri::Error),
+#[derive(Clone, Debug, Serialize)]
+struct TransformPayload {
+    path: String,
+    transform: transform::ImageTransform,
 }
 
 struct AppState {
@@ -66,12 +69,31 @@ struct AppState {
     aspect_ratio: Mutex<HashMap<String, f64>>, // per-window aspect ratio
     adjusting_resize: Mutex<HashSet<String>>,  // per-window resize guard
     aspect_toggle: Mutex<Option<CheckMenuItem<Wry>>>,
+    click_through_toggle: Mutex<Option<CheckMenuItem<Wry>>>,
+    recursive_toggle: Mutex<Option<CheckMenuItem<Wry>>>,
+    wrap_toggle: Mutex<Option<CheckMenuItem<Wry>>>,
+    fit_on_open_toggle: Mutex<Option<CheckMenuItem<Wry>>>,
+    siblings_toggle: Mutex<Option<CheckMenuItem<Wry>>>,
     pending_save: Mutex<HashMap<String, async_runtime::JoinHandle<()>>>,
     selections: Mutex<HashMap<String, SelectionState>>, // per-window selections
     last_focused_window: Mutex<Option<String>>,         // label of last focused window
     window_counter: AtomicUsize,
+    watchers: Mutex<HashMap<String, watcher::ActiveWatch>>, // per-window active file watch
+    pending_reload: Mutex<HashMap<String, watcher::PendingReload>>,
+    aspect_locks: Mutex<HashMap<String, bool>>, // per-window aspect lock, overrides the default
+    opacities: Mutex<HashMap<String, u8>>,      // per-window opacity in percent
+    click_through: Mutex<HashSet<String>>,      // windows letting clicks pass through
+    unpinned: Mutex<HashSet<String>>,           // windows taken off always-on-top
+    hotkey_status: Mutex<Vec<hotkeys::HotkeyStatus>>, // outcome of the last registration
+    menu_items: Mutex<HashMap<&'static str, MenuItem<Wry>>>, // items with a keybinding
+    views: Mutex<HashMap<String, ViewState>>,   // per-window zoom and pan
+    fit_mode_items: Mutex<Vec<(fit::FitMode, CheckMenuItem<Wry>)>>,
+    persistence_paused: AtomicBool, // set while restoring windows and after quit
+    settings_store: OnceLock<Arc<store::QueuedStore<store::FileStore>>>, // opened in setup
 }
 
+/// Bundle identifier from `tauri.conf.json`, for paths needed before Tauri starts.
+const APP_IDENTIFIER: &str = "com.havesomecode.float";
 const LEGACY_APP_NAME: &str = "Always On Top";
 const LEGACY_IDENTIFIER: &str = "com.example.always-on-top";
 
@@ -95,25 +117,6 @@ fn legacy_settings_candidates() -> Vec<PathBuf> {
     candidates
 }
 
-fn is_image_path(path: &str) -> bool {
-    let ext = PathBuf::from(path)
-        .extension()
-        .and_then(|s| s.to_str())
-        .map(|s| s.to_ascii_lowercase());
-    matches!(
-        ext.as_deref(),
-        Some("png")
-            | Some("jpg")
-            | Some("jpeg")
-            | Some("gif")
-            | Some("webp")
-            | Some("bmp")
-            | Some("tif")
-            | Some("tiff")
-            | Some("heic")
-    )
-}
-
 impl Default for AppState {
     fn default() -> Self {
         Self {
@@ -121,10 +124,27 @@ impl Default for AppState {
             aspect_ratio: Mutex::new(HashMap::new()),
             adjusting_resize: Mutex::new(HashSet::new()),
             aspect_toggle: Mutex::new(None),
+            click_through_toggle: Mutex::new(None),
+            recursive_toggle: Mutex::new(None),
+            wrap_toggle: Mutex::new(None),
+            fit_on_open_toggle: Mutex::new(None),
+            siblings_toggle: Mutex::new(None),
             pending_save: Mutex::new(HashMap::new()),
             selections: Mutex::new(HashMap::new()),
             last_focused_window: Mutex::new(None),
             window_counter: AtomicUsize::new(0),
+            watchers: Mutex::new(HashMap::new()),
+            pending_reload: Mutex::new(HashMap::new()),
+            aspect_locks: Mutex::new(HashMap::new()),
+            opacities: Mutex::new(HashMap::new()),
+            click_through: Mutex::new(HashSet::new()),
+            unpinned: Mutex::new(HashSet::new()),
+            hotkey_status: Mutex::new(Vec::new()),
+            menu_items: Mutex::new(HashMap::new()),
+            views: Mutex::new(HashMap::new()),
+            fit_mode_items: Mutex::new(Vec::new()),
+            persistence_paused: AtomicBool::new(false),
+            settings_store: OnceLock::new(),
         }
     }
 }
@@ -140,31 +160,85 @@ fn config_path(app: &AppHandle) -> Result<PathBuf, Error> {
     let dest = dir.join("settings.json");
     if !dest.exists() {
         for candidate in legacy_settings_candidates() {
-            if candidate.exists() {
-                let _ = fs::copy(&candidate, &dest);
-                break;
+            if !candidate.exists() {
+                continue;
+            }
+            let migrated = fs::read(&candidate)
+                .map_err(|err| float_core::Error::io(&candidate, err))
+                .and_then(|bytes| migrate::upgrade(&bytes))
+                .and_then(|st| store::FileStore::new(&dest).save(&st));
+            match migrated {
+                Ok(()) => {
+                    log::info!(
+                        target: "settings",
+                        "migrated legacy settings from={} to={}",
+                        candidate.display(),
+                        dest.display()
+                    );
+                    break;
+                }
+                Err(err) => log::warn!(
+                    target: "settings",
+                    "legacy migration failed path={}: {err}",
+                    candidate.display()
+                ),
             }
         }
     }
     Ok(dest)
 }
 
+/// `settings.json` behind the background writer, opened on first use.
+fn settings_store(app: &AppHandle) -> Result<Arc<store::QueuedStore<store::FileStore>>, Error> {
+    let state = app.try_state::<AppState>().ok_or(Error::NoState)?;
+    if let Some(store) = state.settings_store.get() {
+        return Ok(store.clone());
+    }
+    let store = store::QueuedStore::start(store::FileStore::new(config_path(app)?));
+    let handle = app.clone();
+    store.on_error(move |err| error::report_app(&handle, &err.into()));
+    Ok(state.settings_store.get_or_init(|| store).clone())
+}
+
 fn load_state(app: &AppHandle) -> PersistedState {
-    if let Ok(path) = config_path(app) {
-        if path.exists() {
-            if let Ok(bytes) = fs::read(path) {
-                if let Ok(s) = serde_json::from_slice::<PersistedState>(&bytes) {
-                    return s;
-                }
+    let store = match settings_store(app) {
+        Ok(store) => store,
+        Err(_) => return PersistedState::default(),
+    };
+    match store.load() {
+        Ok(st) => st.unwrap_or_default(),
+        Err(err @ float_core::Error::Io { .. }) => {
+            log::warn!(target: "settings", "can't read settings: {err}");
+            PersistedState::default()
+        }
+        // Keep the file for the user instead of overwriting it with defaults.
+        Err(err) => {
+            let path = store.inner().path();
+            match store.inner().back_up() {
+                Some(backup) => log::warn!(
+                    target: "settings",
+                    "unreadable path={} backup={}: {err}",
+                    path.display(),
+                    backup.display()
+                ),
+                None => log::warn!(
+                    target: "settings",
+                    "unreadable path={}: {err}",
+                    path.display()
+                ),
             }
+            PersistedState::default()
         }
     }
-    PersistedState::default()
 }
 
 fn logical_outer_size(win: &WebviewWindow) -> Option<(f64, f64)> {
     if let (Ok(size), Ok(scale_factor)) = (win.outer_size(), win.scale_factor()) {
-        let safe_scale = if scale_factor > 0.0 { scale_factor } else { 1.0 };
+        let safe_scale = if scale_factor > 0.0 {
+            scale_factor
+        } else {
+            1.0
+        };
         return Some((
             (size.width as f64) / safe_scale,
             (size.height as f64) / safe_scale,
@@ -174,6 +248,12 @@ fn logical_outer_size(win: &WebviewWindow) -> Option<(f64, f64)> {
 }
 
 fn save_state(app: &AppHandle, win: &WebviewWindow, mut st: PersistedState) -> Result<(), Error> {
+    if let Some(state) = app.try_state::<AppState>() {
+        if state.persistence_paused.load(Ordering::SeqCst) {
+            log::debug!(target: "settings", "save skipped while paused label={}", win.label());
+            return Ok(());
+        }
+    }
     if let Some((logical_w, logical_h)) = logical_outer_size(win) {
         st.window_w = Some(logical_w);
         st.window_h = Some(logical_h);
@@ -183,9 +263,26 @@ fn save_state(app: &AppHandle, win: &WebviewWindow, mut st: PersistedState) -> R
         st.window_h = Some(size.height as f64);
         st.window_size_units = Some(WindowSizeUnits::Physical);
     }
-    let path = config_path(app)?;
-    fs::write(path, serde_json::to_vec_pretty(&st)?)?;
-    Ok(())
+    if let Some((x, y, monitor)) = placement::capture(win) {
+        st.window_x = Some(x);
+        st.window_y = Some(y);
+        st.window_monitor = monitor;
+    }
+    st.windows = session::collect(app);
+    log::debug!(
+        target: "settings",
+        "queued save label={} windows={}",
+        win.label(),
+        st.windows.len()
+    );
+    Ok(settings_store(app)?.save(&st)?)
+}
+
+/// Saves `st`, showing a failure in `win`'s HUD instead of returning it.
+fn persist(app: &AppHandle, win: &WebviewWindow, st: PersistedState) {
+    if let Err(err) = save_state(app, win, st) {
+        error::report(win, None, &err);
+    }
 }
 
 fn schedule_size_save(app: AppHandle, label: String, win: WebviewWindow) {
@@ -201,10 +298,10 @@ fn schedule_size_save(app: AppHandle, label: String, win: WebviewWindow) {
             sleep(Duration::from_millis(500)).await;
             if let Some(state) = app_for_task.try_state::<AppState>() {
                 let st = state.settings.lock().clone();
-                let _ = save_state(&app_for_task, &win_for_task, st);
+                persist(&app_for_task, &win_for_task, st);
             } else {
                 let st = load_state(&app_for_task);
-                let _ = save_state(&app_for_task, &win_for_task, st);
+                persist(&app_for_task, &win_for_task, st);
             }
             if let Some(state) = app_for_task.try_state::<AppState>() {
                 state.pending_save.lock().remove(&label_for_task);
@@ -214,7 +311,8 @@ fn schedule_size_save(app: AppHandle, label: String, win: WebviewWindow) {
     }
 }
 
-fn spawn_empty_window(app: &AppHandle) -> Result<(), Error> {
+/// Creates a secondary window with the persisted size and the usual event wiring.
+fn build_window(app: &AppHandle) -> Result<WebviewWindow, Error> {
     let window = tauri::WebviewWindowBuilder::new(
         app,
         next_window_label(app),
@@ -226,6 +324,7 @@ fn spawn_empty_window(app: &AppHandle) -> Result<(), Error> {
     .decorations(false)
     .inner_size(400.0, 400.0)
     .build()?;
+    log::info!(target: "window", "opened label={}", window.label());
 
     apply_initial_window_state(app, &window, false);
     wire_window_events(app, &window);
@@ -235,34 +334,67 @@ fn spawn_empty_window(app: &AppHandle) -> Result<(), Error> {
             .lock()
             .replace(window.label().to_string());
     }
+    Ok(window)
+}
+
+fn spawn_empty_window(app: &AppHandle) -> Result<(), Error> {
+    build_window(app)?;
     Ok(())
 }
 
 fn reset_cache(app: &AppHandle) -> Result<(), Error> {
+    log::info!(target: "settings", "resetting settings and cache");
     if let Some(state) = app.try_state::<AppState>() {
+        // Closing windows below would otherwise save their sessions again.
+        state.persistence_paused.store(true, Ordering::SeqCst);
         // Cancel pending saves to avoid rewriting the file after deletion.
         for (_label, handle) in state.pending_save.lock().drain() {
             handle.abort();
         }
+        for (_label, pending) in state.pending_reload.lock().drain() {
+            pending.abort();
+        }
+        state.watchers.lock().clear();
         *state.settings.lock() = PersistedState::default();
         state.aspect_ratio.lock().clear();
         state.adjusting_resize.lock().clear();
         state.selections.lock().clear();
+        state.aspect_locks.lock().clear();
+        state.opacities.lock().clear();
+        state.click_through.lock().clear();
+        state.unpinned.lock().clear();
+        state.views.lock().clear();
         state.last_focused_window.lock().take();
         // Sync menu toggle to defaults
-        if let Some(toggle) = state.aspect_toggle.lock().clone() {
-            let _ = toggle.set_checked(false);
+        for toggle in [
+            &state.aspect_toggle,
+            &state.click_through_toggle,
+            &state.recursive_toggle,
+            &state.wrap_toggle,
+            &state.siblings_toggle,
+            &state.fit_on_open_toggle,
+        ] {
+            if let Some(toggle) = toggle.lock().clone() {
+                let _ = toggle.set_checked(false);
+            }
         }
+        sync_fit_mode_items(&state, fit::FitMode::default());
     }
-    if let Ok(path) = config_path(app) {
-        if path.exists() {
-            fs::remove_file(path)?;
-        }
+    if let Ok(store) = settings_store(app) {
+        store.clear()?;
+    }
+    if let Err(err) = clipboard::clear(app) {
+        error::report_app(app, &err);
     }
+    hotkeys::register(app); // back to the default global shortcuts
+    keybindings::apply(app, &BTreeMap::new());
     for (_, window) in app.webview_windows() {
         let _ = window.close();
     }
     spawn_empty_window(app)?;
+    if let Some(state) = app.try_state::<AppState>() {
+        state.persistence_paused.store(false, Ordering::SeqCst);
+    }
     Ok(())
 }
 
@@ -287,23 +419,272 @@ fn active_file_for_window(app: &AppHandle, label: &str) -> Option<String> {
     None
 }
 
+/// The rotation and flip remembered for `path`.
+fn image_transform(app: &AppHandle, path: &str) -> transform::ImageTransform {
+    match app.try_state::<AppState>() {
+        Some(state) => state
+            .settings
+            .lock()
+            .transforms
+            .get(path)
+            .copied()
+            .unwrap_or_default(),
+        None => transform::ImageTransform::default(),
+    }
+}
+
+/// Whether the window keeps its aspect ratio on resize. Windows without their own choice
+/// (sessions saved before per-window locks) follow the default, the most recent choice.
+fn aspect_locked(state: &AppState, label: &str) -> bool {
+    match state.aspect_locks.lock().get(label) {
+        Some(locked) => *locked,
+        None => state.settings.lock().aspect_lock,
+    }
+}
+
+/// Sets the window's own aspect lock, leaving the default for new windows alone, and
+/// saves its session. Returns the new lock.
+fn set_window_aspect_lock(
+    app: &AppHandle,
+    window: &WebviewWindow,
+    enabled: Option<bool>,
+) -> Result<bool, Error> {
+    let state = app.try_state::<AppState>().ok_or(Error::NoWindow)?;
+    let label = window.label().to_string();
+    let enabled = enabled.unwrap_or_else(|| !aspect_locked(&state, &label));
+    state.aspect_locks.lock().insert(label.clone(), enabled);
+    if state.last_focused_window.lock().as_deref() == Some(label.as_str()) {
+        if let Some(toggle) = state.aspect_toggle.lock().clone() {
+            let _ = toggle.set_checked(enabled);
+        }
+    }
+    let st = state.settings.lock().clone();
+    save_state(app, window, st)?;
+    Ok(enabled)
+}
+
+/// Applies `percent` opacity to the window and remembers it for its session.
+fn set_window_opacity(app: &AppHandle, window: &WebviewWindow, percent: u8) {
+    if let Some(state) = app.try_state::<AppState>() {
+        state
+            .opacities
+            .lock()
+            .insert(window.label().to_string(), percent);
+    }
+    opacity::set(window, percent);
+}
+
+/// Sets `window`'s opacity and saves it with the window's session. The default for new
+/// windows only changes from Settings.
+fn choose_opacity(app: &AppHandle, window: &WebviewWindow, percent: u8) -> Result<(), Error> {
+    set_window_opacity(app, window, percent.min(100));
+    let st = match app.try_state::<AppState>() {
+        Some(state) => state.settings.lock().clone(),
+        None => load_state(app),
+    };
+    save_state(app, window, st)
+}
+
+/// Percent the opacity shortcuts step by, and the lowest they go: below that a window
+/// is hard to find again.
+const OPACITY_STEP: u8 = 10;
+const MIN_STEPPED_OPACITY: u8 = 10;
+
+fn window_opacity(state: &AppState, label: &str) -> u8 {
+    state.opacities.lock().get(label).copied().unwrap_or(100)
+}
+
+/// Fades the focused window in (`delta > 0`) or out by one step.
+fn step_opacity(app: &AppHandle, delta: i16) {
+    let win = match focused_window(app) {
+        Some(win) => win,
+        None => return,
+    };
+    let current = match app.try_state::<AppState>() {
+        Some(state) => window_opacity(&state, win.label()),
+        None => 100,
+    };
+    let percent = (i16::from(current) + delta * i16::from(OPACITY_STEP))
+        .clamp(i16::from(MIN_STEPPED_OPACITY), 100) as u8;
+    if let Err(err) = choose_opacity(app, &win, percent) {
+        error::report(&win, None, &err);
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct ClickThroughPayload {
+    label: String,
+    enabled: bool,
+}
+
+/// Lets clicks pass through the window to the apps below it ("ghost mode"), or stops.
+fn set_click_through(app: &AppHandle, window: &WebviewWindow, enabled: bool) {
+    if let Err(err) = window.set_ignore_cursor_events(enabled) {
+        error::report(window, None, &err.into());
+        return;
+    }
+    let label = window.label().to_string();
+    if let Some(state) = app.try_state::<AppState>() {
+        {
+            let mut ghosts = state.click_through.lock();
+            if enabled {
+                ghosts.insert(label.clone());
+            } else {
+                ghosts.remove(&label);
+            }
+        }
+        sync_click_through_toggle(&state);
+    }
+    if let Err(err) = window.emit(
+        "click-through-changed",
+        ClickThroughPayload {
+            label: label.clone(),
+            enabled,
+        },
+    ) {
+        log::warn!(target: "window", "can't emit click-through-changed label={label}: {err}");
+    }
+    schedule_size_save(app.clone(), label, window.clone());
+}
+
+/// The window the user last worked in, which global shortcuts and `float ctl` act on:
+/// Float may not be focused at all when they fire.
+fn last_focused_webview(app: &AppHandle) -> Option<WebviewWindow> {
+    app.try_state::<AppState>()
+        .and_then(|state| state.last_focused_window.lock().clone())
+        .and_then(|label| app.get_webview_window(&label))
+        .or_else(|| focused_window(app))
+}
+
+/// Flips click-through on the last focused window: a ghost window never takes focus, so
+/// that is still the one the user was looking at.
+fn toggle_click_through(app: &AppHandle) {
+    let window = match last_focused_webview(app) {
+        Some(window) => window,
+        None => return,
+    };
+    let enabled = match app.try_state::<AppState>() {
+        Some(state) => state.click_through.lock().contains(window.label()),
+        None => false,
+    };
+    set_click_through(app, &window, !enabled);
+}
+
+/// Hides every window when any is showing, otherwise shows them all again without
+/// taking focus from the app in front.
+fn toggle_all_windows_visible(app: &AppHandle) {
+    let windows = app.webview_windows();
+    let any_visible = windows
+        .values()
+        .any(|window| window.is_visible().unwrap_or(false));
+    for window in windows.values() {
+        let _ = if any_visible {
+            window.hide()
+        } else {
+            window.show()
+        };
+    }
+}
+
+/// Unpins the last focused window so other apps can cover it, or pins it again.
+fn toggle_always_on_top(app: &AppHandle) {
+    let window = match last_focused_webview(app) {
+        Some(window) => window,
+        None => return,
+    };
+    if let Some(state) = app.try_state::<AppState>() {
+        let label = window.label().to_string();
+        let mut unpinned = state.unpinned.lock();
+        let pin = unpinned.contains(&label);
+        if window.set_always_on_top(pin).is_ok() {
+            if pin {
+                unpinned.remove(&label);
+            } else {
+                unpinned.insert(label);
+            }
+        }
+    }
+}
+
+/// Checks the View → Fit Mode item for `mode` and unchecks the others.
+fn sync_fit_mode_items(state: &AppState, mode: fit::FitMode) {
+    for (item_mode, item) in state.fit_mode_items.lock().iter() {
+        let _ = item.set_checked(*item_mode == mode);
+    }
+}
+
+/// Makes `mode` the one Fit to Image uses and fits the focused window with it.
+fn select_fit_mode(app: &AppHandle, mode: fit::FitMode) {
+    let update = SettingsUpdate {
+        fit_mode: Some(mode),
+        ..Default::default()
+    };
+    if let Err(err) = set_settings(app.clone(), update) {
+        error::report_app(app, &err);
+    }
+    if let Some(win) = focused_window(app) {
+        if let Err(err) = fit_now(app.clone(), win.clone(), Some(mode)) {
+            error::report(&win, None, &err);
+        }
+    }
+}
+
+/// The menu check mirrors the last focused window's click-through.
+fn sync_click_through_toggle(state: &AppState) {
+    let label = state.last_focused_window.lock().clone();
+    let enabled = match label {
+        Some(label) => state.click_through.lock().contains(&label),
+        None => false,
+    };
+    if let Some(toggle) = state.click_through_toggle.lock().clone() {
+        let _ = toggle.set_checked(enabled);
+    }
+}
+
 fn emit_active_file(window: &WebviewWindow, payload: ActiveFilePayload) {
-    let _ = window.emit("active-file-changed", payload.clone());
+    if let Err(err) = window.emit("active-file-changed", payload.clone()) {
+        log::warn!(target: "window", "can't emit active-file-changed label={}: {err}", window.label());
+    }
     // Backward compatibility with the previous event name
-    let _ = window.emit(
+    if let Err(err) = window.emit(
         "file-selected",
         ActiveFilePayload {
             path: payload.path.clone(),
             index: None,
             total: None,
+            revision: payload.revision,
+            missing: payload.missing,
+            wrap: payload.wrap,
+            transform: payload.transform,
         },
-    );
+    ) {
+        log::warn!(target: "window", "can't emit file-selected label={}: {err}", window.label());
+    }
+}
+
+/// Modification time in milliseconds, used to bust the webview's image cache.
+fn file_revision(path: &Path) -> Option<u64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_millis() as u64)
+}
+
+/// Why a window's active file is being applied. Only a file the user picked refits the
+/// window when Fit When Image Changes is on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ApplyReason {
+    /// Opened, navigated to or pasted.
+    Open,
+    /// Brought back with its window's session, which already restored the size.
+    Restore,
+    /// The same image again: changed on disk, or re-pointed at a saved copy.
+    Reload,
 }
 
 fn apply_active_file(
     app: &AppHandle,
     window: &WebviewWindow,
     selection: &SelectionState,
+    reason: ApplyReason,
 ) -> Option<String> {
     let path_str = selection.files.get(selection.active)?.clone();
     if !is_image_path(&path_str) {
@@ -311,32 +692,56 @@ fn apply_active_file(
     }
     let path = PathBuf::from(&path_str);
     if let Some(name) = path.file_name().and_then(|s| s.to_str()) {
-        let _ = window.set_title(&format!("Float — {}", name));
+        if let Err(err) = window.set_title(&format!("Float — {}", name)) {
+            log::warn!(target: "window", "can't set title label={}: {err}", window.label());
+        }
     }
 
+    watcher::watch(app, window.label(), &path_str);
+    let missing = !path.exists();
+    log::info!(
+        target: "selection",
+        "active label={} index={} total={} missing={missing} path={path_str}",
+        window.label(),
+        selection.active,
+        selection.files.len()
+    );
+
     // Cache aspect ratio per window
-    if let Ok((w, h)) = image::image_dimensions(&path) {
-        if h > 0 {
-            if let Some(state) = app.try_state::<AppState>() {
-                state
-                    .aspect_ratio
-                    .lock()
-                    .insert(window.label().to_string(), w as f64 / h as f64);
+    let transform = image_transform(app, &path_str);
+    let dimensions = decode::image_dimensions(&path).map(|size| transform.apply_to(size));
+    if let Ok((w, h)) = dimensions {
+        if w > 0 && h > 0 {
+            let fit_on_open = match app.try_state::<AppState>() {
+                Some(state) => {
+                    state
+                        .aspect_ratio
+                        .lock()
+                        .insert(window.label().to_string(), w as f64 / h as f64);
+                    state.settings.lock().fit_on_open
+                }
+                None => false,
+            };
+            if fit_on_open && reason == ApplyReason::Open {
+                fit_to_image(app, window, (w as f64, h as f64), None);
             }
         }
     }
 
     // Persist active file and window size
-    if let Some(state) = app.try_state::<AppState>() {
+    let wrap = if let Some(state) = app.try_state::<AppState>() {
         let mut st = state.settings.lock().clone();
         st.last_file = Some(path_str.clone());
-        let _ = save_state(app, window, st.clone());
+        persist(app, window, st.clone());
+        let wrap = st.wrap_navigation;
         *state.settings.lock() = st;
+        wrap
     } else {
         let mut st = load_state(app);
         st.last_file = Some(path_str.clone());
-        let _ = save_state(app, window, st.clone());
-    }
+        persist(app, window, st.clone());
+        st.wrap_navigation
+    };
 
     emit_active_file(
         window,
@@ -344,13 +749,29 @@ fn apply_active_file(
             path: Some(path_str.clone()),
             index: Some(selection.active),
             total: Some(selection.files.len()),
+            revision: file_revision(&path),
+            missing,
+            wrap,
+            transform,
         },
     );
+    if let (Err(err), false) = (dimensions, missing) {
+        error::report(window, Some(&path_str), &err);
+    }
 
     Some(path_str)
 }
 
 fn apply_selection(app: &AppHandle, window: &WebviewWindow, files: Vec<String>) -> Option<String> {
+    apply_selection_for(app, window, files, ApplyReason::Open)
+}
+
+fn apply_selection_for(
+    app: &AppHandle,
+    window: &WebviewWindow,
+    files: Vec<String>,
+    reason: ApplyReason,
+) -> Option<String> {
     let files: Vec<String> = files.into_iter().filter(|p| is_image_path(p)).collect();
     if files.is_empty() {
         emit_active_file(
@@ -359,42 +780,127 @@ fn apply_selection(app: &AppHandle, window: &WebviewWindow, files: Vec<String>)
                 path: None,
                 index: None,
                 total: Some(0),
+                revision: None,
+                missing: false,
+                wrap: false,
+                transform: transform::ImageTransform::default(),
             },
         );
+        watcher::unwatch(app, window.label());
         return None;
     }
-    let selection = SelectionState { files, active: 0 };
+    let browse_siblings = app
+        .try_state::<AppState>()
+        .map(|state| state.settings.lock().browse_siblings)
+        .unwrap_or(false);
+    let selection = if files.len() == 1 && browse_siblings {
+        selection::with_siblings(&files[0])
+    } else {
+        SelectionState { files, active: 0 }
+    };
     if let Some(state) = app.try_state::<AppState>() {
         state
             .selections
             .lock()
             .insert(window.label().to_string(), selection.clone());
     }
-    apply_active_file(app, window, &selection)
+    apply_active_file(app, window, &selection, reason)
+}
+
+/// Adds `files` to the end of the window's selection (skipping ones already in it) and
+/// makes the first added file active. Starts a new selection when there is none.
+fn append_to_selection(
+    app: &AppHandle,
+    window: &WebviewWindow,
+    files: Vec<String>,
+) -> Option<String> {
+    let state = app.try_state::<AppState>()?;
+    let mut selections = state.selections.lock();
+    let sel = match selections.get_mut(window.label()) {
+        Some(sel) if !sel.files.is_empty() => sel,
+        _ => {
+            drop(selections);
+            return apply_selection(app, window, files);
+        }
+    };
+    if !sel.append(files) {
+        return None;
+    }
+    // Saving snapshots every selection, so release the lock first.
+    let sel = sel.clone();
+    drop(selections);
+    apply_active_file(app, window, &sel, ApplyReason::Open)
+}
+
+/// Turns dropped or command-line paths into image files: folders contribute their images
+/// (recursively when Include Subfolders is on), everything else goes through the image filter.
+fn expand_paths(app: &AppHandle, paths: &[PathBuf]) -> Vec<String> {
+    let recursive = app
+        .try_state::<AppState>()
+        .map(|state| state.settings.lock().folder_recursive)
+        .unwrap_or(false);
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            files.extend(selection::images_in_folder(path, recursive));
+        } else {
+            let path_str = path.to_string_lossy().to_string();
+            if is_image_path(&path_str) {
+                files.push(path_str);
+            }
+        }
+    }
+    files
+}
+
+/// Re-applies the window's active file after it changed on disk, re-measuring its
+/// aspect ratio and refreshing the webview (or showing the missing state).
+fn reload_active_file(app: &AppHandle, window: &WebviewWindow) {
+    let selection = app
+        .try_state::<AppState>()
+        .and_then(|state| state.selections.lock().get(window.label()).cloned());
+    if let Some(selection) = selection {
+        let _ = apply_active_file(app, window, &selection, ApplyReason::Reload);
+    }
 }
 
 fn navigate_selection(app: &AppHandle, window: &WebviewWindow, delta: isize) -> Option<String> {
     if let Some(state) = app.try_state::<AppState>() {
         let mut selections = state.selections.lock();
         if let Some(sel) = selections.get_mut(window.label()) {
-            let len = sel.files.len();
-            if len == 0 {
-                return None;
-            }
-            let current = sel.active as isize;
-            let next = current.saturating_add(delta);
-            let bounded = next.clamp(0, (len as isize) - 1) as usize;
-            if bounded != sel.active {
-                sel.active = bounded;
-                return apply_active_file(app, window, sel);
+            let wrap = state.settings.lock().wrap_navigation;
+            if sel.step(delta, wrap) {
+                let sel = sel.clone();
+                drop(selections);
+                return apply_active_file(app, window, &sel, ApplyReason::Open);
             }
         }
     }
     None
 }
 
-fn apply_initial_window_state(app: &AppHandle, window: &WebviewWindow, load_last_file: bool) {
+/// Applies the persisted size and, when `load_last_file` is set, restores the saved
+/// multi-window session or else the legacy single last file. Returns whether a session
+/// was restored.
+fn apply_initial_window_state(
+    app: &AppHandle,
+    window: &WebviewWindow,
+    load_last_file: bool,
+) -> bool {
     let _ = window.set_always_on_top(true);
+    // New windows start from the current default; changing it later leaves them alone.
+    if let Some(state) = app.try_state::<AppState>() {
+        let default_lock = state.settings.lock().aspect_lock;
+        state
+            .aspect_locks
+            .lock()
+            .entry(window.label().to_string())
+            .or_insert(default_lock);
+        let default_opacity = state.settings.lock().opacity;
+        if let Some(percent) = default_opacity {
+            set_window_opacity(app, window, percent);
+        }
+    }
 
     let st = load_state(app);
     if let (Some(w), Some(h)) = (st.window_w, st.window_h) {
@@ -402,7 +908,11 @@ fn apply_initial_window_state(app: &AppHandle, window: &WebviewWindow, load_last
             WindowSizeUnits::Logical => Some((w, h)),
             WindowSizeUnits::Physical => {
                 if let Ok(scale_factor) = window.scale_factor() {
-                    let safe_scale = if scale_factor > 0.0 { scale_factor } else { 1.0 };
+                    let safe_scale = if scale_factor > 0.0 {
+                        scale_factor
+                    } else {
+                        1.0
+                    };
                     Some((w / safe_scale, h / safe_scale))
                 } else {
                     None
@@ -410,20 +920,30 @@ fn apply_initial_window_state(app: &AppHandle, window: &WebviewWindow, load_last
             }
         };
         if let Some((logical_w, logical_h)) = logical_size {
-            let _ = window.set_size(tauri::Size::Logical(tauri::LogicalSize {
+            if let Err(err) = window.set_size(tauri::Size::Logical(tauri::LogicalSize {
                 width: logical_w,
                 height: logical_h,
-            }));
+            })) {
+                error::report(window, None, &err.into());
+            }
         }
     }
 
-    if load_last_file {
-        if let Some(p) = st.last_file.clone() {
-            if is_image_path(&p) && PathBuf::from(&p).exists() {
-                let _ = apply_selection(app, window, vec![p]);
-            }
+    if !load_last_file {
+        return false;
+    }
+    if session::restore(app, window, &st.windows) {
+        return true;
+    }
+    if let (Some(x), Some(y)) = (st.window_x, st.window_y) {
+        placement::restore(window, x, y, st.window_monitor.as_ref());
+    }
+    if let Some(p) = st.last_file.clone() {
+        if is_image_path(&p) && PathBuf::from(&p).exists() {
+            let _ = apply_selection_for(app, window, vec![p], ApplyReason::Restore);
         }
     }
+    false
 }
 
 fn wire_window_events(app_handle: &AppHandle, window: &WebviewWindow) {
@@ -436,18 +956,22 @@ fn wire_window_events(app_handle: &AppHandle, window: &WebviewWindow) {
                 if adjusting.contains(&label) {
                     return;
                 }
-                let st = state.settings.lock().clone();
-                if st.aspect_lock {
-                    if let Some(r) = state.aspect_ratio.lock().get(&label).copied() {
-                        if r.is_finite() && r > 0.0 {
-                            let new_w = size.width as f64;
-                            let new_h = (new_w / r).round().max(1.0);
+                if aspect_locked(&state, &label) {
+                    let ratio = state.aspect_ratio.lock().get(&label).copied();
+                    let win = app_for_event.get_webview_window(&label);
+                    if let (Some(r), Some(win)) = (ratio, win) {
+                        // `size` is physical; the new size is set in logical units.
+                        let scale = win.scale_factor().unwrap_or(1.0);
+                        let logical = (size.width as f64 / scale, size.height as f64 / scale);
+                        if let Some((width, height)) = aspect::constrain(logical, r) {
                             adjusting.insert(label.clone());
-                            if let Some(win) = app_for_event.get_webview_window(&label) {
-                                let _ = win.set_size(tauri::Size::Logical(tauri::LogicalSize {
-                                    width: new_w,
-                                    height: new_h,
-                                }));
+                            if let Err(err) =
+                                win.set_size(tauri::Size::Logical(tauri::LogicalSize {
+                                    width,
+                                    height,
+                                }))
+                            {
+                                error::report(&win, None, &err.into());
                             }
                             adjusting.remove(&label);
                         }
@@ -458,21 +982,59 @@ fn wire_window_events(app_handle: &AppHandle, window: &WebviewWindow) {
                 }
             }
         }
+        WindowEvent::Moved(_) => {
+            if let Some(win) = app_for_event.get_webview_window(&label) {
+                schedule_size_save(app_for_event.clone(), label.clone(), win);
+            }
+        }
         WindowEvent::Focused(true) => {
+            log::debug!(target: "window", "focused label={label}");
             if let Some(state) = app_for_event.try_state::<AppState>() {
                 *state.last_focused_window.lock() = Some(label.clone());
+                // The menu check mirrors the focused window's own aspect lock.
+                if let Some(toggle) = state.aspect_toggle.lock().clone() {
+                    let _ = toggle.set_checked(aspect_locked(&state, &label));
+                }
+                sync_click_through_toggle(&state);
             }
             if let Some(win) = app_for_event.get_webview_window(&label) {
                 if let Some(path) = active_file_for_window(&app_for_event, &label) {
                     if let Some(state) = app_for_event.try_state::<AppState>() {
                         let mut st = state.settings.lock().clone();
                         st.last_file = Some(path);
-                        let _ = save_state(&app_for_event, &win, st.clone());
+                        persist(&app_for_event, &win, st.clone());
                         *state.settings.lock() = st;
                     }
                 }
             }
         }
+        WindowEvent::DragDrop(DragDropEvent::Drop { paths, .. }) => {
+            let files = expand_paths(&app_for_event, paths);
+            if files.is_empty() {
+                return;
+            }
+            if let Some(win) = app_for_event.get_webview_window(&label) {
+                // Shift+drop appends to the current selection instead of replacing it.
+                if modifiers::shift_held() {
+                    let _ = append_to_selection(&app_for_event, &win, files);
+                } else {
+                    let _ = apply_selection(&app_for_event, &win, files);
+                }
+            }
+        }
+        WindowEvent::Destroyed => {
+            log::info!(target: "window", "closed label={label}");
+            watcher::unwatch(&app_for_event, &label);
+            // Labels get reused, so a later window must not inherit this one's state.
+            if let Some(state) = app_for_event.try_state::<AppState>() {
+                state.selections.lock().remove(&label);
+                state.aspect_locks.lock().remove(&label);
+                state.opacities.lock().remove(&label);
+                state.click_through.lock().remove(&label);
+                state.unpinned.lock().remove(&label);
+                state.views.lock().remove(&label);
+            }
+        }
         _ => {}
     });
 }
@@ -499,33 +1061,91 @@ fn next_file(app: AppHandle) -> Option<String> {
 }
 
 #[tauri::command]
-fn load_image_data(path: String) -> Result<String, String> {
-    if !is_image_path(&path) {
-        return Err("unsupported file type".into());
-    }
-    let path_buf = PathBuf::from(&path);
-    if !path_buf.exists() {
-        return Err("file does not exist".into());
-    }
-    let bytes = fs::read(&path_buf).map_err(|e| format!("read error: {e}"))?;
-    let mime = match path_buf
-        .extension()
-        .and_then(|s| s.to_str())
-        .map(|s| s.to_ascii_lowercase())
-        .as_deref()
+fn paste_image(app: AppHandle) -> Result<Option<String>, Error> {
+    let win = focused_window(&app).ok_or(Error::NoWindow)?;
+    let path = clipboard::save_clipboard_image(&app)?;
+    let applied = apply_selection(&app, &win, vec![path.to_string_lossy().to_string()]);
+    // The paste this one replaced is usually shown nowhere else now.
+    prune_pastes(&app);
+    Ok(applied)
+}
+
+/// Deletes the cached pastes that no window shows and the saved session doesn't
+/// mention, so the paste folder doesn't grow with every Paste Image.
+fn prune_pastes(app: &AppHandle) {
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    let mut keep: HashSet<PathBuf> = state
+        .selections
+        .lock()
+        .values()
+        .flat_map(|sel| sel.files.iter().map(PathBuf::from))
+        .collect();
     {
-        Some("png") => "image/png",
-        Some("jpg") | Some("jpeg") => "image/jpeg",
-        Some("gif") => "image/gif",
-        Some("webp") => "image/webp",
-        _ => "application/octet-stream",
+        let st = state.settings.lock();
+        keep.extend(st.last_file.iter().map(PathBuf::from));
+        keep.extend(
+            st.windows
+                .values()
+                .flat_map(|session| session.files.iter().map(PathBuf::from)),
+        );
+    }
+    match clipboard::prune(app, &keep) {
+        Ok(0) => {}
+        Ok(removed) => log::debug!(target: "clipboard", "pruned pastes count={removed}"),
+        Err(err) => log::warn!(target: "clipboard", "can't prune pastes: {err}"),
+    }
+}
+
+/// File → Save As…: copies the focused window's active file somewhere the user picks.
+/// Pasted images are re-pointed at the saved copy so they survive a cache reset.
+fn save_active_file_as(app: &AppHandle) -> Result<Option<String>, Error> {
+    let win = match focused_window(app) {
+        Some(win) => win,
+        None => return Ok(None),
     };
-    let encoded = general_purpose::STANDARD.encode(bytes);
-    Ok(format!("data:{mime};base64,{encoded}"))
+    let source = match active_file_for_window(app, win.label()) {
+        Some(path) => PathBuf::from(path),
+        None => return Ok(None),
+    };
+    let mut picker = app.dialog().file().set_parent(&win);
+    if let Some(name) = source.file_name().and_then(|s| s.to_str()) {
+        picker = picker.set_file_name(name);
+    }
+    if let Some(ext) = source.extension().and_then(|s| s.to_str()) {
+        picker = picker.add_filter("Image", &[ext]);
+    }
+    let dest = match picker.blocking_save_file().and_then(|p| p.into_path().ok()) {
+        Some(dest) => dest,
+        None => return Ok(None),
+    };
+    if dest != source {
+        fs::copy(&source, &dest)?;
+    }
+    let dest_str = dest.to_string_lossy().to_string();
+    if clipboard::is_pasted(app, &source) {
+        if let Some(state) = app.try_state::<AppState>() {
+            let updated = state.selections.lock().get_mut(win.label()).map(|sel| {
+                if let Some(active) = sel.files.get_mut(sel.active) {
+                    *active = dest_str.clone();
+                }
+                sel.clone()
+            });
+            if let Some(sel) = updated {
+                let _ = apply_active_file(app, &win, &sel, ApplyReason::Reload);
+            }
+        }
+    }
+    Ok(Some(dest_str))
 }
 
 #[tauri::command]
-fn fit_now(app: AppHandle, window: WebviewWindow) -> Result<(), String> {
+fn fit_now(
+    app: AppHandle,
+    window: WebviewWindow,
+    mode: Option<fit::FitMode>, // defaults to the chosen fit mode
+) -> Result<(), Error> {
     let path = active_file_for_window(&app, window.label())
         .map(PathBuf::from)
         .or_else(|| {
@@ -542,55 +1162,63 @@ fn fit_now(app: AppHandle, window: WebviewWindow) -> Result<(), String> {
         None => return Ok(()),
     };
 
-    let img = image::image_dimensions(&path)
-        .map_err(|e| format!("failed to read image dimensions: {e}"))?;
+    let img = decode::image_dimensions(&path)?;
+    let img = image_transform(&app, &path.to_string_lossy()).apply_to(img);
     let (img_w, img_h) = (img.0 as f64, img.1 as f64);
     if img_w <= 0.0 || img_h <= 0.0 {
         return Ok(());
     }
-    let aspect = img_w / img_h;
-
-    // Anchor on the current larger window dimension and adjust the other down to match aspect.
-    // Convert to logical units first so high-DPI windows don't double in size when resizing.
-    if let (Ok(size), Ok(scale_factor)) = (window.outer_size(), window.scale_factor()) {
-        let cur_w = (size.width as f64) / scale_factor;
-        let cur_h = (size.height as f64) / scale_factor;
-        let min_dim = 50.0_f64;
-        let (mut new_w, mut new_h) = if cur_w >= cur_h {
-            let mut target_w = cur_w;
-            let mut target_h = target_w / aspect;
-            if target_h > cur_h && target_h > 0.0 {
-                let scale = cur_h / target_h;
-                target_w *= scale;
-                target_h = cur_h;
-            }
-            (target_w, target_h)
-        } else {
-            let mut target_h = cur_h;
-            let mut target_w = target_h * aspect;
-            if target_w > cur_w && target_w > 0.0 {
-                let scale = cur_w / target_w;
-                target_h *= scale;
-                target_w = cur_w;
-            }
-            (target_w, target_h)
-        };
+    fit_to_image(&app, &window, (img_w, img_h), mode);
+    Ok(())
+}
 
-        new_w = new_w.max(min_dim);
-        new_h = new_h.max(min_dim);
+/// Resizes the window for an image of `image` pixels with `mode`, or the chosen fit mode
+/// when `None`, and records the image's aspect ratio for the aspect lock.
+fn fit_to_image(
+    app: &AppHandle,
+    window: &WebviewWindow,
+    image: (f64, f64),
+    mode: Option<fit::FitMode>,
+) {
+    let (img_w, img_h) = image;
+    let mode = match mode {
+        Some(mode) => mode,
+        None => match app.try_state::<AppState>() {
+            Some(state) => state.settings.lock().fit_mode,
+            None => load_state(app).fit_mode,
+        },
+    };
 
-        let _ = window.set_size(tauri::Size::Logical(tauri::LogicalSize {
-            width: new_w,
-            height: new_h,
-        }));
+    // Work in logical units so high-DPI windows don't double in size when resizing.
+    if let (Ok(size), Ok(scale_factor)) = (window.outer_size(), window.scale_factor()) {
+        let current = (
+            (size.width as f64) / scale_factor,
+            (size.height as f64) / scale_factor,
+        );
+        let bounds = placement::work_area_size(window);
+        let (new_w, new_h) = fit::target_size(mode, image, current, scale_factor, bounds);
+        log::debug!(
+            target: "fit",
+            "label={} mode={mode:?} image={img_w}x{img_h} current={:.0}x{:.0} scale={scale_factor} bounds={bounds:?} target={new_w:.0}x{new_h:.0}",
+            window.label(),
+            current.0,
+            current.1
+        );
+        // Record the ratio first so an aspect-locked window keeps the fitted size.
         if let Some(state) = app.try_state::<AppState>() {
             state
                 .aspect_ratio
                 .lock()
-                .insert(window.label().to_string(), aspect);
+                .insert(window.label().to_string(), img_w / img_h);
+        }
+        placement::keep_in_work_area(window, new_w, new_h);
+        if let Err(err) = window.set_size(tauri::Size::Logical(tauri::LogicalSize {
+            width: new_w,
+            height: new_h,
+        })) {
+            error::report(window, None, &err.into());
         }
     }
-    Ok(())
 }
 
 #[tauri::command]
@@ -602,34 +1230,246 @@ fn get_settings(app: AppHandle) -> PersistedState {
     }
 }
 
-#[derive(Deserialize)]
+/// Whether the calling window is in click-through mode, for the HUD badge after a reload.
+#[tauri::command]
+fn get_click_through(app: AppHandle, window: WebviewWindow) -> bool {
+    match app.try_state::<AppState>() {
+        Some(state) => state.click_through.lock().contains(window.label()),
+        None => false,
+    }
+}
+
+/// The calling window's zoom and pan, restored by the frontend when it loads.
+#[tauri::command]
+fn get_view(app: AppHandle, window: WebviewWindow) -> Option<ViewState> {
+    let state = app.try_state::<AppState>()?;
+    let view = state.views.lock().get(window.label()).copied();
+    view
+}
+
+/// Records the calling window's zoom and pan for its session.
+#[tauri::command]
+fn set_view(app: AppHandle, window: WebviewWindow, view: ViewState) {
+    if !(view.zoom.is_finite() && view.zoom > 0.0 && view.x.is_finite() && view.y.is_finite()) {
+        return;
+    }
+    if let Some(state) = app.try_state::<AppState>() {
+        let label = window.label().to_string();
+        let previous = state.views.lock().insert(label.clone(), view);
+        if previous != Some(view) {
+            schedule_size_save(app.clone(), label, window);
+        }
+    }
+}
+
+/// Rotates or flips the window's image and remembers the result for its file. Every
+/// window showing the file takes the new aspect ratio, and aspect-locked ones turn on
+/// their side with it.
+#[tauri::command]
+fn transform_image(
+    app: AppHandle,
+    window: WebviewWindow,
+    action: transform::TransformAction,
+) -> Result<transform::ImageTransform, Error> {
+    let path = active_file_for_window(&app, window.label()).ok_or(Error::NoImage)?;
+    let state = app.try_state::<AppState>().ok_or(Error::NoState)?;
+    let mut st = state.settings.lock().clone();
+    let before = st.transforms.get(&path).copied().unwrap_or_default();
+    let after = before.then(action);
+    if after.is_identity() {
+        st.transforms.remove(&path);
+    } else {
+        st.transforms.insert(path.clone(), after);
+    }
+    save_state(&app, &window, st.clone())?;
+    *state.settings.lock() = st;
+
+    match decode::image_dimensions(Path::new(&path)).map(|s| after.apply_to(s)) {
+        Ok((w, h)) if w > 0 && h > 0 => {
+            for win in app.webview_windows().values() {
+                let label = win.label();
+                if active_file_for_window(&app, label).as_deref() != Some(path.as_str()) {
+                    continue;
+                }
+                state
+                    .aspect_ratio
+                    .lock()
+                    .insert(label.to_string(), w as f64 / h as f64);
+                if before.swaps_axes() != after.swaps_axes() && aspect_locked(&state, label) {
+                    swap_window_axes(win);
+                }
+            }
+        }
+        Ok(_) => {}
+        Err(err) => error::report(&window, Some(&path), &err),
+    }
+    if let Err(err) = app.emit(
+        "image-transform-changed",
+        TransformPayload {
+            path,
+            transform: after,
+        },
+    ) {
+        log::warn!(target: "window", "can't emit image-transform-changed: {err}");
+    }
+    Ok(after)
+}
+
+/// Trades the window's width and height, kept inside its monitor's work area.
+fn swap_window_axes(window: &WebviewWindow) {
+    if let (Ok(size), Ok(scale_factor)) = (window.outer_size(), window.scale_factor()) {
+        let swapped = (
+            (size.height as f64) / scale_factor,
+            (size.width as f64) / scale_factor,
+        );
+        let bounds = placement::work_area_size(window);
+        let (new_w, new_h) =
+            fit::target_size(fit::FitMode::Aspect, swapped, swapped, scale_factor, bounds);
+        placement::keep_in_work_area(window, new_w, new_h);
+        if let Err(err) = window.set_size(tauri::Size::Logical(tauri::LogicalSize {
+            width: new_w,
+            height: new_h,
+        })) {
+            error::report(window, None, &err.into());
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct ZoomPayload {
+    label: String,
+    action: &'static str, // "in", "out", "fit" or "actual"
+}
+
+#[derive(Default, Deserialize)]
 struct SettingsUpdate {
     aspect_lock: Option<bool>,
+    folder_recursive: Option<bool>,
+    wrap_navigation: Option<bool>,
+    browse_siblings: Option<bool>,
+    opacity: Option<u8>, // applied to the focused window
+    global_shortcuts: Option<BTreeMap<HotkeyAction, String>>,
+    keybindings: Option<BTreeMap<String, String>>, // replaces the whole table
+    fit_mode: Option<fit::FitMode>,
+    fit_on_open: Option<bool>,
 }
 
 #[tauri::command]
-fn set_settings(app: AppHandle, update: SettingsUpdate) -> Result<PersistedState, String> {
-    let win = focused_window(&app).ok_or("missing window")?;
+fn set_settings(app: AppHandle, update: SettingsUpdate) -> Result<PersistedState, Error> {
+    let win = focused_window(&app).ok_or(Error::NoWindow)?;
     let mut st = if let Some(state) = app.try_state::<AppState>() {
         state.settings.lock().clone()
     } else {
         load_state(&app)
     };
+    if update.keybindings.is_some() || update.global_shortcuts.is_some() {
+        // Menu and global shortcuts share the keyboard, so either change checks both.
+        let table = update.keybindings.as_ref().unwrap_or(&st.keybindings);
+        let global = update
+            .global_shortcuts
+            .as_ref()
+            .unwrap_or(&st.global_shortcuts);
+        keybindings::validate(table, global)
+            .map_err(|problems| Error::InvalidSettings(problems.join("\n")))?;
+    }
     if let Some(v) = update.aspect_lock {
         st.aspect_lock = v;
         if let Some(state) = app.try_state::<AppState>() {
+            state.aspect_locks.lock().insert(win.label().to_string(), v);
             if let Some(toggle) = state.aspect_toggle.lock().clone() {
                 let _ = toggle.set_checked(v);
             }
         }
     }
-    save_state(&app, &win, st.clone()).map_err(|e| e.to_string())?;
+    if let Some(mode) = update.fit_mode {
+        st.fit_mode = mode;
+        if let Some(state) = app.try_state::<AppState>() {
+            sync_fit_mode_items(&state, mode);
+        }
+    }
+    if let Some(v) = update.opacity {
+        let v = v.min(100);
+        st.opacity = Some(v);
+        set_window_opacity(&app, &win, v);
+    }
+    if let Some(state) = app.try_state::<AppState>() {
+        let toggles = [
+            (
+                update.folder_recursive,
+                &mut st.folder_recursive,
+                &state.recursive_toggle,
+            ),
+            (
+                update.wrap_navigation,
+                &mut st.wrap_navigation,
+                &state.wrap_toggle,
+            ),
+            (
+                update.browse_siblings,
+                &mut st.browse_siblings,
+                &state.siblings_toggle,
+            ),
+            (
+                update.fit_on_open,
+                &mut st.fit_on_open,
+                &state.fit_on_open_toggle,
+            ),
+        ];
+        for (value, field, toggle) in toggles {
+            if let Some(v) = value {
+                *field = v;
+                if let Some(toggle) = toggle.lock().clone() {
+                    let _ = toggle.set_checked(v);
+                }
+            }
+        }
+    }
+    let keybindings_changed = match update.keybindings {
+        Some(table) => {
+            st.keybindings = table;
+            true
+        }
+        None => false,
+    };
+    let shortcuts_changed = match update.global_shortcuts {
+        Some(shortcuts) => {
+            st.global_shortcuts = shortcuts;
+            true
+        }
+        None => false,
+    };
+    save_state(&app, &win, st.clone())?;
     if let Some(state) = app.try_state::<AppState>() {
         *state.settings.lock() = st.clone();
     }
+    if shortcuts_changed {
+        hotkeys::register(&app);
+    }
+    if keybindings_changed {
+        keybindings::apply(&app, &st.keybindings);
+    }
     Ok(st)
 }
 
+/// The menu commands with their current and default shortcuts, for the Shortcuts section.
+#[tauri::command]
+fn get_keybindings(app: AppHandle) -> Vec<keybindings::KeybindingInfo> {
+    let overrides = match app.try_state::<AppState>() {
+        Some(state) => state.settings.lock().keybindings.clone(),
+        None => load_state(&app).keybindings,
+    };
+    keybindings::table(&overrides)
+}
+
+/// Every global shortcut with its accelerator and whether it could be registered.
+#[tauri::command]
+fn get_global_shortcuts(app: AppHandle) -> Vec<hotkeys::HotkeyStatus> {
+    match app.try_state::<AppState>() {
+        Some(state) => state.hotkey_status.lock().clone(),
+        None => Vec::new(),
+    }
+}
+
 enum SelectionTarget {
     CurrentWindow,
     NewWindow,
@@ -683,7 +1523,11 @@ fn pick_and_apply_selection(app: AppHandle, target: SelectionTarget) -> Option<S
                     }
                 }
                 SelectionTarget::NewWindow => {
-                    return spawn_new_window_with_files(&app, vec![test_path]);
+                    return spawn_new_window_with_files(
+                        &app,
+                        vec![test_path],
+                        &cli::WindowOptions::default(),
+                    );
                 }
             }
         } else {
@@ -706,7 +1550,56 @@ fn pick_and_apply_selection(app: AppHandle, target: SelectionTarget) -> Option<S
                 None
             }
         }
-        SelectionTarget::NewWindow => spawn_new_window_with_files(&app, files),
+        SelectionTarget::NewWindow => {
+            spawn_new_window_with_files(&app, files, &cli::WindowOptions::default())
+        }
+    }
+}
+
+fn pick_and_apply_folder(app: AppHandle) -> Option<String> {
+    let focus = focused_window(&app);
+    let picker = match focus.as_ref() {
+        Some(win) => app.dialog().file().set_parent(win),
+        None => app.dialog().file(),
+    };
+    let dir = picker.blocking_pick_folder()?.into_path().ok()?;
+    let recursive = app
+        .try_state::<AppState>()
+        .map(|state| state.settings.lock().folder_recursive)
+        .unwrap_or(false);
+    let files = selection::images_in_folder(&dir, recursive);
+    if files.is_empty() {
+        return None;
+    }
+    let win = focus.or_else(|| app.get_webview_window("main"))?;
+    let selection = SelectionState { files, active: 0 };
+    if let Some(state) = app.try_state::<AppState>() {
+        state
+            .selections
+            .lock()
+            .insert(win.label().to_string(), selection.clone());
+    }
+    apply_active_file(&app, &win, &selection, ApplyReason::Open)
+}
+
+/// Flips a boolean setting from its menu check item and persists it.
+fn toggle_setting(
+    app: &AppHandle,
+    toggle: Option<CheckMenuItem<Wry>>,
+    field: fn(&mut PersistedState) -> &mut bool,
+) {
+    if let Some(state) = app.try_state::<AppState>() {
+        let mut s = state.settings.lock().clone();
+        let desired = match toggle.as_ref().map(|t| t.is_checked()) {
+            // Check items flip themselves before the event fires.
+            Some(Ok(checked)) => checked,
+            _ => !*field(&mut s),
+        };
+        *field(&mut s) = desired;
+        if let Some(win) = focused_window(app) {
+            persist(app, &win, s.clone());
+        }
+        *state.settings.lock() = s;
     }
 }
 
@@ -726,132 +1619,364 @@ fn next_window_label(app: &AppHandle) -> String {
     }
 }
 
-fn spawn_new_window_with_files(app: &AppHandle, files: Vec<String>) -> Option<String> {
+fn spawn_new_window_with_files(
+    app: &AppHandle,
+    files: Vec<String>,
+    options: &cli::WindowOptions,
+) -> Option<String> {
     if files.is_empty() {
         return None;
     }
-    let label = next_window_label(app);
-    let window =
-        tauri::WebviewWindowBuilder::new(app, &label, WebviewUrl::App("index.html".into()))
-            .title("Float")
-            .visible(true)
-            .resizable(true)
-            .decorations(false)
-            .inner_size(400.0, 400.0)
-            .build()
-            .ok()?;
+    let window = build_window(app)
+        .inspect_err(|err| error::report_app(app, err))
+        .ok()?;
+    open_with_options(app, &window, files, options)
+}
 
-    apply_initial_window_state(app, &window, false);
-    wire_window_events(app, &window);
-    if let Some(state) = app.try_state::<AppState>() {
-        state
-            .last_focused_window
-            .lock()
-            .replace(window.label().to_string());
+/// Applies command-line geometry and opacity to `window`, opens `files` in it and
+/// fits it when `--fit` was given.
+fn open_with_options(
+    app: &AppHandle,
+    window: &WebviewWindow,
+    files: Vec<String>,
+    options: &cli::WindowOptions,
+) -> Option<String> {
+    if let Some((width, height)) = options.size {
+        if let Err(err) =
+            window.set_size(tauri::Size::Logical(tauri::LogicalSize { width, height }))
+        {
+            error::report(window, None, &err.into());
+        }
+    }
+    if let Some((x, y)) = options.position {
+        let position = tauri::Position::Logical(tauri::LogicalPosition { x, y });
+        if let Err(err) = window.set_position(position) {
+            error::report(window, None, &err.into());
+        }
+    }
+    if let Some(percent) = options.opacity {
+        set_window_opacity(app, window, percent);
+    }
+    if options.aspect_lock {
+        if let Some(state) = app.try_state::<AppState>() {
+            state
+                .aspect_locks
+                .lock()
+                .insert(window.label().to_string(), true);
+        }
+    }
+    let active = if files.is_empty() {
+        None
+    } else {
+        apply_selection(app, window, files)
+    };
+    if options.fit {
+        if let Err(err) = fit_now(app.clone(), window.clone(), None) {
+            error::report(window, None, &err);
+        }
+    }
+    active
+}
+
+/// Opens the command-line paths: all of them as one selection, or one window per path
+/// with `--separate-windows`. The first group goes to `main` when given, the rest to new
+/// windows.
+fn open_cli_paths(app: &AppHandle, main: Option<&WebviewWindow>, args: &cli::Cli) {
+    let options = args.window_options();
+    let groups: Vec<Vec<String>> = if args.separate_windows {
+        args.paths
+            .iter()
+            .map(|path| expand_paths(app, std::slice::from_ref(path)))
+            .collect()
+    } else {
+        vec![expand_paths(app, &args.paths)]
+    };
+    let mut groups = groups.into_iter();
+    if let Some(main) = main {
+        let _ = open_with_options(app, main, groups.next().unwrap_or_default(), &options);
+    }
+    for files in groups {
+        let _ = spawn_new_window_with_files(app, files, &options);
+    }
+}
+
+/// Handles a second launch forwarded over the control socket: its paths open in new
+/// windows, and a bare launch brings the last focused window to the front.
+fn open_forwarded_launch(app: &AppHandle, args: &cli::Cli) {
+    if args.paths.is_empty() {
+        let label = app
+            .try_state::<AppState>()
+            .and_then(|state| state.last_focused_window.lock().clone());
+        let win = label
+            .and_then(|label| app.get_webview_window(&label))
+            .or_else(|| focused_window(app));
+        match win {
+            Some(win) => {
+                if args.aspect_lock {
+                    if let Some(state) = app.try_state::<AppState>() {
+                        state
+                            .aspect_locks
+                            .lock()
+                            .insert(win.label().to_string(), true);
+                    }
+                }
+                let _ = win.unminimize();
+                let _ = win.show();
+                let _ = win.set_focus();
+            }
+            None => {
+                let _ = spawn_empty_window(app);
+            }
+        }
+        return;
     }
-    apply_selection(app, &window, files)
+    open_cli_paths(app, None, args);
 }
 
 fn main() {
+    let args = cli::parse();
+    if let Some(cli::Command::Ctl { json, action }) = args.command {
+        std::process::exit(ctl::run(action, json));
+    }
+    // Hand the launch to a running instance instead of starting a second process.
+    let instance = match ipc::claim(&ipc::Request::Launch { args: args.clone() }) {
+        Ok(ipc::Claim::Primary(instance)) => Some(instance),
+        Ok(ipc::Claim::Forwarded(response)) if response.ok => return,
+        Ok(ipc::Claim::Forwarded(response)) => {
+            eprintln!("float: {}", response.error.unwrap_or_default());
+            std::process::exit(1);
+        }
+        Err(err) => {
+            eprintln!("float: control socket unavailable: {err}");
+            None
+        }
+    };
+    let (log_plugin, log_problems) = logging::plugin();
     tauri::Builder::default()
+        .plugin(log_plugin)
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .manage(AppState::default())
-        .setup(|app| {
+        .register_asynchronous_uri_scheme_protocol(protocol::SCHEME, |_ctx, request, responder| {
+            // Serve off the main thread so large files don't stall the event loop.
+            std::thread::spawn(move || responder.respond(protocol::handle(&request)));
+        })
+        .setup(move |app| {
             let app_handle = app.handle().clone();
+            log::info!("starting version={}", app.package_info().version);
+            for problem in log_problems {
+                log::warn!("{problem}");
+            }
 
             // Build native menu with platform shortcuts and toggles.
+            let initial = load_state(&app_handle);
+            // A hand-edited table may still be off; the menu keeps whatever parses.
+            if let Err(problems) =
+                keybindings::validate(&initial.keybindings, &initial.global_shortcuts)
+            {
+                for problem in problems {
+                    log::warn!("keybindings: {problem}");
+                }
+            }
+            let recursive_toggle =
+                CheckMenuItemBuilder::with_id("folder_recursive_toggle", "Include Subfolders")
+                    .checked(initial.folder_recursive)
+                    .build(&app_handle)?;
             let file_menu = SubmenuBuilder::new(&app_handle, "File")
-                .item(
-                    &MenuItemBuilder::with_id("new_window", "New Window…")
-                        .accelerator(if cfg!(target_os = "macos") {
-                            "Cmd+T"
-                        } else {
-                            "Ctrl+T"
-                        })
-                        .build(&app_handle)?,
-                )
-                .item(
-                    &MenuItemBuilder::with_id("open", "Open…")
-                        .accelerator(if cfg!(target_os = "macos") {
-                            "Cmd+O"
-                        } else {
-                            "Ctrl+O"
-                        })
-                        .build(&app_handle)?,
-                )
-                .item(
-                    &MenuItemBuilder::with_id("close_window", "Close Window")
-                        .accelerator(if cfg!(target_os = "macos") {
-                            "Cmd+W"
-                        } else {
-                            "Ctrl+W"
-                        })
-                        .build(&app_handle)?,
-                )
-                .item(
-                    &MenuItemBuilder::with_id("reset_cache", "Reset Cache")
-                        .accelerator(if cfg!(target_os = "macos") {
-                            "Cmd+Shift+Backspace"
-                        } else {
-                            "Ctrl+Shift+Backspace"
-                        })
-                        .build(&app_handle)?,
-                )
-                .item(
-                    &MenuItemBuilder::with_id("quit", "Quit")
-                        .accelerator(if cfg!(target_os = "macos") {
-                            "Cmd+Q"
-                        } else {
-                            "Ctrl+Q"
-                        })
-                        .build(&app_handle)?,
-                )
+                .item(&keybindings::menu_item(
+                    &app_handle,
+                    &initial.keybindings,
+                    "new_window",
+                )?)
+                .item(&keybindings::menu_item(
+                    &app_handle,
+                    &initial.keybindings,
+                    "open",
+                )?)
+                .item(&keybindings::menu_item(
+                    &app_handle,
+                    &initial.keybindings,
+                    "open_folder",
+                )?)
+                .item(&recursive_toggle)
+                .item(&keybindings::menu_item(
+                    &app_handle,
+                    &initial.keybindings,
+                    "save_as",
+                )?)
+                .item(&keybindings::menu_item(
+                    &app_handle,
+                    &initial.keybindings,
+                    "close_window",
+                )?)
+                .item(&keybindings::menu_item(
+                    &app_handle,
+                    &initial.keybindings,
+                    "settings",
+                )?)
+                .item(&keybindings::menu_item(
+                    &app_handle,
+                    &initial.keybindings,
+                    "reset_cache",
+                )?)
+                .item(&keybindings::menu_item(
+                    &app_handle,
+                    &initial.keybindings,
+                    "quit",
+                )?)
+                .build()?;
+
+            // Cmd/Ctrl+V reaches the webview, which pastes text into a field or else an
+            // image into the window. macOS webviews only get it through the standard items.
+            let mut edit_menu = SubmenuBuilder::new(&app_handle, "Edit");
+            if cfg!(target_os = "macos") {
+                edit_menu = edit_menu.cut().copy().paste().select_all().separator();
+            }
+            let edit_menu = edit_menu
+                .item(&keybindings::menu_item(
+                    &app_handle,
+                    &initial.keybindings,
+                    "paste_image",
+                )?)
                 .build()?;
 
             let aspect_toggle =
                 CheckMenuItemBuilder::with_id("aspect_lock_toggle", "Lock aspect ratio on resize")
-                    .checked(load_state(&app_handle).aspect_lock)
+                    .checked(initial.aspect_lock)
+                    .build(&app_handle)?;
+            let wrap_toggle =
+                CheckMenuItemBuilder::with_id("wrap_navigation_toggle", "Wrap Around at Ends")
+                    .checked(initial.wrap_navigation)
+                    .build(&app_handle)?;
+            let siblings_toggle = CheckMenuItemBuilder::with_id(
+                "browse_siblings_toggle",
+                "Browse Folder of Opened File",
+            )
+            .checked(initial.browse_siblings)
+            .build(&app_handle)?;
+            // No accelerator: the window can't take keys while it is a ghost, so the
+            // global shortcut registered below toggles it instead.
+            let click_through_toggle =
+                CheckMenuItemBuilder::with_id("click_through_toggle", "Click-Through (Ghost Mode)")
                     .build(&app_handle)?;
+            let fit_on_open_toggle =
+                CheckMenuItemBuilder::with_id("fit_on_open_toggle", "Fit When Image Changes")
+                    .checked(initial.fit_on_open)
+                    .build(&app_handle)?;
+            let mut fit_mode_menu = SubmenuBuilder::new(&app_handle, "Fit Mode");
+            let mut fit_mode_items = Vec::new();
+            for mode in fit::FitMode::ALL {
+                let item = CheckMenuItemBuilder::with_id(mode.menu_id(), mode.label())
+                    .checked(initial.fit_mode == mode)
+                    .build(&app_handle)?;
+                fit_mode_menu = fit_mode_menu.item(&item);
+                fit_mode_items.push((mode, item));
+            }
+            let fit_mode_menu = fit_mode_menu.build()?;
             let view_menu = SubmenuBuilder::new(&app_handle, "View")
-                .item(
-                    &MenuItemBuilder::with_id("fit_now", "Fit to Image Now")
-                        .accelerator(if cfg!(target_os = "macos") {
-                            "Cmd+F"
-                        } else {
-                            "Ctrl+F"
-                        })
-                        .build(&app_handle)?,
-                )
-                .item(
-                    &MenuItemBuilder::with_id("previous_file", "Previous File")
-                        .accelerator(if cfg!(target_os = "macos") {
-                            "Alt+Cmd+["
-                        } else {
-                            "Ctrl+["
-                        })
-                        .build(&app_handle)?,
-                )
-                .item(
-                    &MenuItemBuilder::with_id("next_file", "Next File")
-                        .accelerator(if cfg!(target_os = "macos") {
-                            "Alt+Cmd+]"
-                        } else {
-                            "Ctrl+]"
-                        })
-                        .build(&app_handle)?,
-                )
-                .item(&aspect_toggle);
+                .item(&keybindings::menu_item(
+                    &app_handle,
+                    &initial.keybindings,
+                    "fit_now",
+                )?)
+                .item(&fit_mode_menu)
+                .item(&fit_on_open_toggle)
+                .item(&keybindings::menu_item(
+                    &app_handle,
+                    &initial.keybindings,
+                    "previous_file",
+                )?)
+                .item(&keybindings::menu_item(
+                    &app_handle,
+                    &initial.keybindings,
+                    "next_file",
+                )?)
+                .item(&wrap_toggle)
+                .item(&siblings_toggle)
+                .item(&aspect_toggle)
+                .separator()
+                .item(&keybindings::menu_item(
+                    &app_handle,
+                    &initial.keybindings,
+                    "zoom_in",
+                )?)
+                .item(&keybindings::menu_item(
+                    &app_handle,
+                    &initial.keybindings,
+                    "zoom_out",
+                )?)
+                .item(&keybindings::menu_item(
+                    &app_handle,
+                    &initial.keybindings,
+                    "zoom_actual",
+                )?)
+                .item(&keybindings::menu_item(
+                    &app_handle,
+                    &initial.keybindings,
+                    "zoom_fit",
+                )?)
+                .separator()
+                .item(&keybindings::menu_item(
+                    &app_handle,
+                    &initial.keybindings,
+                    "rotate_cw",
+                )?)
+                .item(&keybindings::menu_item(
+                    &app_handle,
+                    &initial.keybindings,
+                    "rotate_ccw",
+                )?)
+                .item(&keybindings::menu_item(
+                    &app_handle,
+                    &initial.keybindings,
+                    "flip_horizontal",
+                )?)
+                .item(&keybindings::menu_item(
+                    &app_handle,
+                    &initial.keybindings,
+                    "flip_vertical",
+                )?)
+                .separator()
+                .item(&keybindings::menu_item(
+                    &app_handle,
+                    &initial.keybindings,
+                    "opacity_up",
+                )?)
+                .item(&keybindings::menu_item(
+                    &app_handle,
+                    &initial.keybindings,
+                    "opacity_down",
+                )?)
+                .item(&keybindings::menu_item(
+                    &app_handle,
+                    &initial.keybindings,
+                    "opacity_reset",
+                )?)
+                .item(&click_through_toggle);
+            let help_menu = SubmenuBuilder::new(&app_handle, "Help")
+                .item(&MenuItemBuilder::with_id("reveal_logs", "Reveal Logs").build(&app_handle)?)
+                .build()?;
             let app_menu = MenuBuilder::new(&app_handle)
                 .item(&file_menu)
+                .item(&edit_menu)
                 .item(&view_menu.build()?)
+                .item(&help_menu)
                 .build()?;
             app.set_menu(app_menu)?;
             if let Some(state) = app_handle.try_state::<AppState>() {
                 *state.aspect_toggle.lock() = Some(aspect_toggle.clone());
+                *state.click_through_toggle.lock() = Some(click_through_toggle.clone());
+                *state.fit_mode_items.lock() = fit_mode_items;
+                *state.recursive_toggle.lock() = Some(recursive_toggle.clone());
+                *state.wrap_toggle.lock() = Some(wrap_toggle.clone());
+                *state.siblings_toggle.lock() = Some(siblings_toggle.clone());
+                *state.fit_on_open_toggle.lock() = Some(fit_on_open_toggle.clone());
             }
 
+            let has_session = !initial.windows.is_empty();
             if let Some(state) = app_handle.try_state::<AppState>() {
-                *state.settings.lock() = load_state(&app_handle);
+                *state.settings.lock() = initial;
                 state
                     .window_counter
                     .store(1, std::sync::atomic::Ordering::SeqCst);
@@ -860,32 +1985,140 @@ fn main() {
             let win = app_handle
                 .get_webview_window("main")
                 .expect("main window exists");
-            apply_initial_window_state(&app_handle, &win, true);
+            // A saved session comes back even when paths are given (they then open in
+            // new windows); the legacy single last file only when there are none.
+            let restore = !args.no_restore && (has_session || args.paths.is_empty());
+            let restored = apply_initial_window_state(&app_handle, &win, restore);
+            // `--aspect-lock` is per window like the other options: restored windows keep
+            // their own lock unless the launch has no paths, which applies it to `main`.
+            if restored && args.paths.is_empty() && args.aspect_lock {
+                if let Some(state) = app_handle.try_state::<AppState>() {
+                    state
+                        .aspect_locks
+                        .lock()
+                        .insert(win.label().to_string(), true);
+                }
+            }
             wire_window_events(&app_handle, &win);
+            open_cli_paths(&app_handle, (!restored).then_some(&win), &args);
+            prune_pastes(&app_handle);
+            hotkeys::register(&app_handle);
+            if let Some(instance) = instance {
+                ipc::serve(app_handle.clone(), instance);
+            }
 
             Ok(())
         })
         .on_menu_event(|app, event| match event.id().as_ref() {
+            "reveal_logs" => {
+                if let Err(err) = logging::reveal(app) {
+                    error::report_app(app, &err);
+                }
+            }
             "open" => {
                 let handle = app.clone();
                 async_runtime::spawn(async move {
                     let _ = choose_file(handle).await;
                 });
             }
+            "open_folder" => {
+                let handle = app.clone();
+                async_runtime::spawn(async move {
+                    let _ = pick_and_apply_folder(handle);
+                });
+            }
+            "folder_recursive_toggle" => {
+                if let Some(state) = app.try_state::<AppState>() {
+                    let toggle = state.recursive_toggle.lock().clone();
+                    toggle_setting(app, toggle, |s| &mut s.folder_recursive);
+                }
+            }
+            "wrap_navigation_toggle" => {
+                if let Some(state) = app.try_state::<AppState>() {
+                    let toggle = state.wrap_toggle.lock().clone();
+                    toggle_setting(app, toggle, |s| &mut s.wrap_navigation);
+                }
+            }
+            "fit_on_open_toggle" => {
+                if let Some(state) = app.try_state::<AppState>() {
+                    let toggle = state.fit_on_open_toggle.lock().clone();
+                    toggle_setting(app, toggle, |s| &mut s.fit_on_open);
+                }
+            }
+            "browse_siblings_toggle" => {
+                if let Some(state) = app.try_state::<AppState>() {
+                    let toggle = state.siblings_toggle.lock().clone();
+                    toggle_setting(app, toggle, |s| &mut s.browse_siblings);
+                }
+            }
             "new_window" => {
                 let handle = app.clone();
                 async_runtime::spawn(async move {
                     let _ = pick_and_apply_selection(handle, SelectionTarget::NewWindow);
                 });
             }
+            "save_as" => {
+                let handle = app.clone();
+                async_runtime::spawn(async move {
+                    if let Err(err) = save_active_file_as(&handle) {
+                        error::report_app(&handle, &err);
+                    }
+                });
+            }
+            "paste_image" => {
+                if let Err(err) = paste_image(app.clone()) {
+                    error::report_app(app, &err);
+                }
+            }
             "close_window" => {
                 if let Some(win) = focused_window(app) {
                     let _ = win.close();
                 }
             }
+            "zoom_in" | "zoom_out" | "zoom_fit" | "zoom_actual" => {
+                if let Some(win) = focused_window(app) {
+                    let action = match event.id().as_ref() {
+                        "zoom_in" => "in",
+                        "zoom_out" => "out",
+                        "zoom_fit" => "fit",
+                        _ => "actual",
+                    };
+                    let payload = ZoomPayload {
+                        label: win.label().to_string(),
+                        action,
+                    };
+                    if let Err(err) = win.emit("zoom", payload) {
+                        log::warn!(target: "window", "can't emit zoom label={}: {err}", win.label());
+                    }
+                }
+            }
+            "rotate_cw" | "rotate_ccw" | "flip_horizontal" | "flip_vertical" => {
+                if let Some(win) = focused_window(app) {
+                    let action = match event.id().as_ref() {
+                        "rotate_cw" => transform::TransformAction::RotateCw,
+                        "rotate_ccw" => transform::TransformAction::RotateCcw,
+                        "flip_horizontal" => transform::TransformAction::FlipHorizontal,
+                        _ => transform::TransformAction::FlipVertical,
+                    };
+                    if let Err(err) = transform_image(app.clone(), win.clone(), action) {
+                        error::report(&win, None, &err);
+                    }
+                }
+            }
+            "settings" => {
+                if let Some(win) = focused_window(app) {
+                    if let Err(err) = win.emit("open-settings", win.label()) {
+                        log::warn!(
+                            target: "window",
+                            "can't emit open-settings label={}: {err}",
+                            win.label()
+                        );
+                    }
+                }
+            }
             "reset_cache" => {
                 if let Err(err) = reset_cache(app) {
-                    eprintln!("reset cache failed: {err}");
+                    error::report_app(app, &err);
                 }
             }
             "quit" => {
@@ -895,17 +2128,21 @@ fn main() {
                             if let Some(path) = active_file_for_window(app, &label) {
                                 let mut st = state.settings.lock().clone();
                                 st.last_file = Some(path);
-                                let _ = save_state(app, &win, st.clone());
+                                persist(app, &win, st.clone());
                                 *state.settings.lock() = st;
                             }
                         }
                     }
+                    // Windows closing on the way out must not drop out of the session.
+                    state.persistence_paused.store(true, Ordering::SeqCst);
                 }
                 app.exit(0);
             }
             "fit_now" => {
                 if let Some(win) = focused_window(app) {
-                    let _ = fit_now(app.clone(), win);
+                    if let Err(err) = fit_now(app.clone(), win.clone(), None) {
+                        error::report(&win, None, &err);
+                    }
                 }
             }
             "previous_file" => {
@@ -934,11 +2171,30 @@ fn main() {
                     };
                     s.aspect_lock = new_state;
                     if let Some(win) = focused_window(app) {
-                        let _ = save_state(app, &win, s.clone());
+                        state
+                            .aspect_locks
+                            .lock()
+                            .insert(win.label().to_string(), new_state);
+                        persist(app, &win, s.clone());
                     }
                     *state.settings.lock() = s;
                 }
             }
+            "opacity_up" => step_opacity(app, 1),
+            "opacity_down" => step_opacity(app, -1),
+            "opacity_reset" => {
+                if let Some(win) = focused_window(app) {
+                    if let Err(err) = choose_opacity(app, &win, 100) {
+                        error::report(&win, None, &err);
+                    }
+                }
+            }
+            "click_through_toggle" => toggle_click_through(app),
+            id if id.starts_with("fit_mode_") => {
+                if let Some(mode) = fit::FitMode::ALL.into_iter().find(|m| m.menu_id() == id) {
+                    select_fit_mode(app, mode);
+                }
+            }
             _ => {}
         })
         .invoke_handler(tauri::generate_handler![
@@ -946,10 +2202,37 @@ fn main() {
             fit_now,
             get_settings,
             set_settings,
-            load_image_data,
+            get_click_through,
+            get_global_shortcuts,
+            get_keybindings,
+            get_view,
+            set_view,
+            transform_image,
+            paste_image,
             previous_file,
             next_file
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| match event {
+            // Files dropped on the Dock icon (or opened via Finder) arrive here.
+            #[cfg(target_os = "macos")]
+            tauri::RunEvent::Opened { urls } => {
+                let paths: Vec<PathBuf> = urls
+                    .iter()
+                    .filter_map(|url| url.to_file_path().ok())
+                    .collect();
+                let files = expand_paths(app, &paths);
+                let _ = spawn_new_window_with_files(app, files, &cli::WindowOptions::default());
+            }
+            // The last queued save has to reach the disk before the process ends.
+            tauri::RunEvent::Exit => {
+                if let Some(state) = app.try_state::<AppState>() {
+                    if let Some(store) = state.settings_store.get() {
+                        store.flush();
+                    }
+                }
+            }
+            _ => {}
+        });
 }
@@ -0,0 +1,24 @@
+//! Current keyboard modifier state, read from the OS.
+//!
+//! Native drag-and-drop events don't carry modifiers and the webview rarely has
+//! focus while something is dragged onto it, so we ask the OS directly.
+
+/// Whether Shift is held right now. Always `false` where the OS offers no
+/// synchronous query (Linux).
+#[cfg(target_os = "macos")]
+pub fn shift_held() -> bool {
+    use objc2_app_kit::{NSEvent, NSEventModifierFlags};
+    NSEvent::modifierFlags_class().contains(NSEventModifierFlags::Shift)
+}
+
+#[cfg(target_os = "windows")]
+pub fn shift_held() -> bool {
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{GetAsyncKeyState, VK_SHIFT};
+    // The high bit is set while the key is down.
+    unsafe { (GetAsyncKeyState(VK_SHIFT as i32) as u16 & 0x8000) != 0 }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn shift_held() -> bool {
+    false
+}
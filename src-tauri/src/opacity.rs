@@ -0,0 +1,53 @@
+//! Whole-window transparency. Tauri has no opacity API, so each platform's native
+//! window is adjusted directly.
+
+use tauri::WebviewWindow;
+
+/// Alpha used for 0%, so a fully faded window still renders and can be found again.
+const MIN_ALPHA: f64 = 0.05;
+
+/// Sets the window, frame and image alike, to `percent` (0..=100) opacity.
+pub fn set(window: &WebviewWindow, percent: u8) {
+    let alpha = (f64::from(percent.min(100)) / 100.0).max(MIN_ALPHA);
+    let target = window.clone();
+    // Native window handles may only be touched from the main thread.
+    let _ = window.run_on_main_thread(move || apply(&target, alpha));
+}
+
+#[cfg(target_os = "macos")]
+fn apply(window: &WebviewWindow, alpha: f64) {
+    use objc2_app_kit::NSWindow;
+    if let Ok(ptr) = window.ns_window() {
+        // SAFETY: Tauri hands out this window's live NSWindow and we run on the main thread.
+        let ns_window = unsafe { &*ptr.cast::<NSWindow>() };
+        ns_window.setAlphaValue(alpha);
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn apply(window: &WebviewWindow, alpha: f64) {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        GetWindowLongW, SetLayeredWindowAttributes, SetWindowLongW, GWL_EXSTYLE, LWA_ALPHA,
+        WS_EX_LAYERED,
+    };
+    if let Ok(hwnd) = window.hwnd() {
+        let hwnd = hwnd.0 as _;
+        // Per-window alpha requires a layered window.
+        unsafe {
+            let style = GetWindowLongW(hwnd, GWL_EXSTYLE);
+            SetWindowLongW(hwnd, GWL_EXSTYLE, style | WS_EX_LAYERED as i32);
+            SetLayeredWindowAttributes(hwnd, 0, (alpha * 255.0).round() as u8, LWA_ALPHA);
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn apply(window: &WebviewWindow, alpha: f64) {
+    use gtk::prelude::WidgetExt;
+    if let Ok(gtk_window) = window.gtk_window() {
+        gtk_window.set_opacity(alpha);
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn apply(_window: &WebviewWindow, _alpha: f64) {}
@@ -0,0 +1,281 @@
+//! Window placement across monitors: the outer position is saved in logical units with
+//! the monitor it was on, and restored onto that monitor, or clamped back onto a visible
+//! one when it is gone.
+
+use float_core::settings::MonitorId;
+use tauri::{Monitor, WebviewWindow};
+
+use crate::error;
+
+/// Smallest part of a window (logical px per axis) that must stay on screen.
+const MIN_VISIBLE: f64 = 64.0;
+
+/// macOS places windows in one global space of logical points; Windows and X11 use
+/// virtual-screen pixels, so monitors with different scales are compared in pixels there.
+const LOGICAL_SPACE: bool = cfg!(target_os = "macos");
+
+/// Logical outer position of the window and the monitor it is on.
+pub fn capture(window: &WebviewWindow) -> Option<(f64, f64, Option<MonitorId>)> {
+    let position = window.outer_position().ok()?;
+    let monitor = window.current_monitor().ok().flatten();
+    let scale = match &monitor {
+        Some(monitor) => monitor.scale_factor(),
+        None => window.scale_factor().ok()?,
+    };
+    let scale = if scale > 0.0 { scale } else { 1.0 };
+    Some((
+        position.x as f64 / scale,
+        position.y as f64 / scale,
+        monitor.map(|m| MonitorId {
+            name: m.name().cloned(),
+            scale,
+        }),
+    ))
+}
+
+/// `(x, y, width, height)` in placement units.
+type Rect = (f64, f64, f64, f64);
+
+/// The monitor's work area in placement units.
+fn work_area(monitor: &Monitor) -> Rect {
+    let area = monitor.work_area();
+    area_in_units(
+        (area.position.x, area.position.y),
+        (area.size.width, area.size.height),
+        monitor.scale_factor(),
+        LOGICAL_SPACE,
+    )
+}
+
+/// A work area reported in physical px on a monitor with `scale`, in placement units:
+/// logical points when `logical_space`, else unchanged.
+fn area_in_units(position: (i32, i32), size: (u32, u32), scale: f64, logical_space: bool) -> Rect {
+    let unit = if logical_space && scale > 0.0 {
+        scale
+    } else {
+        1.0
+    };
+    (
+        position.0 as f64 / unit,
+        position.1 as f64 / unit,
+        size.0 as f64 / unit,
+        size.1 as f64 / unit,
+    )
+}
+
+fn overlap(start: f64, len: f64, area_start: f64, area_len: f64) -> f64 {
+    ((start + len).min(area_start + area_len) - start.max(area_start)).max(0.0)
+}
+
+/// Whether enough of the window rect lies inside `area` to grab it again.
+fn visible_in(area: Rect, rect: Rect, min_visible: f64) -> bool {
+    let (ax, ay, aw, ah) = area;
+    let (x, y, w, h) = rect;
+    overlap(x, w, ax, aw) >= min_visible.min(w) && overlap(y, h, ay, ah) >= min_visible.min(h)
+}
+
+/// Moves the window rect fully inside `area`, pinning it to the top-left corner on the
+/// axes where it doesn't fit.
+fn clamp_into(area: Rect, rect: Rect) -> (f64, f64) {
+    let (ax, ay, aw, ah) = area;
+    let (x, y, w, h) = rect;
+    (x.min(ax + aw - w).max(ax), y.min(ay + ah - h).max(ay))
+}
+
+/// Where the window rect goes: where it is when enough of it shows on one of `areas`,
+/// otherwise clamped into `fallback`.
+fn place(rect: Rect, areas: &[Rect], fallback: Rect, min_visible: f64) -> (f64, f64) {
+    if areas
+        .iter()
+        .any(|&area| visible_in(area, rect, min_visible))
+    {
+        (rect.0, rect.1)
+    } else {
+        clamp_into(fallback, rect)
+    }
+}
+
+/// Puts the window at logical `(x, y)` on `monitor` when that display is still
+/// connected, otherwise converting with the saved scale. If too little of the window
+/// would be on screen it is clamped into the saved (or primary) monitor's work area.
+pub fn restore(window: &WebviewWindow, x: f64, y: f64, monitor: Option<&MonitorId>) {
+    let monitors = window.available_monitors().unwrap_or_default();
+    if monitors.is_empty() {
+        let position = tauri::Position::Logical(tauri::LogicalPosition { x, y });
+        if let Err(err) = window.set_position(position) {
+            error::report(window, None, &err.into());
+        }
+        return;
+    }
+    let saved = monitor.and_then(|id| {
+        id.name
+            .as_ref()
+            .and_then(|name| monitors.iter().find(|m| m.name() == Some(name)))
+    });
+    let scale = saved
+        .map(|m| m.scale_factor())
+        .or_else(|| monitor.map(|id| id.scale))
+        .filter(|s| *s > 0.0)
+        .unwrap_or(1.0);
+
+    // The physical size depends on the monitor the window is on right now.
+    let (w, h) = match (window.outer_size(), window.scale_factor()) {
+        (Ok(size), Ok(current)) if current > 0.0 => {
+            (size.width as f64 / current, size.height as f64 / current)
+        }
+        _ => (MIN_VISIBLE, MIN_VISIBLE),
+    };
+    let unit = if LOGICAL_SPACE { 1.0 } else { scale };
+    let rect = (x * unit, y * unit, w * unit, h * unit);
+    let min_visible = MIN_VISIBLE * unit;
+    let areas: Vec<Rect> = monitors.iter().map(work_area).collect();
+    let fallback = saved
+        .cloned()
+        .or_else(|| window.primary_monitor().ok().flatten())
+        .map_or(areas[0], |m| work_area(&m));
+    let (px, py) = place(rect, &areas, fallback, min_visible);
+    set_position(window, px, py);
+}
+
+/// Moves the window to `(x, y)` in placement units.
+fn set_position(window: &WebviewWindow, x: f64, y: f64) {
+    let position = if LOGICAL_SPACE {
+        tauri::Position::Logical(tauri::LogicalPosition { x, y })
+    } else {
+        tauri::Position::Physical(tauri::PhysicalPosition {
+            x: x.round() as i32,
+            y: y.round() as i32,
+        })
+    };
+    if let Err(err) = window.set_position(position) {
+        error::report(window, None, &err.into());
+    }
+}
+
+/// Logical size of the work area of the monitor the window is on.
+pub fn work_area_size(window: &WebviewWindow) -> Option<(f64, f64)> {
+    let monitor = window.current_monitor().ok().flatten()?;
+    let scale = monitor.scale_factor();
+    let scale = if scale > 0.0 { scale } else { 1.0 };
+    let area = monitor.work_area();
+    Some((
+        area.size.width as f64 / scale,
+        area.size.height as f64 / scale,
+    ))
+}
+
+/// Slides a window that is about to become `width`×`height` logical px back inside its
+/// monitor's work area, e.g. before Fit to Image grows it past the screen edge.
+pub fn keep_in_work_area(window: &WebviewWindow, width: f64, height: f64) {
+    let (monitor, position) = match (window.current_monitor(), window.outer_position()) {
+        (Ok(Some(monitor)), Ok(position)) => (monitor, position),
+        _ => return,
+    };
+    let scale = monitor.scale_factor();
+    let scale = if scale > 0.0 { scale } else { 1.0 };
+    let rect = if LOGICAL_SPACE {
+        (
+            position.x as f64 / scale,
+            position.y as f64 / scale,
+            width,
+            height,
+        )
+    } else {
+        (
+            position.x as f64,
+            position.y as f64,
+            width * scale,
+            height * scale,
+        )
+    };
+    let (x, y) = clamp_into(work_area(&monitor), rect);
+    if (x, y) != (rect.0, rect.1) {
+        set_position(window, x, y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 1440×900 pt laptop at 2× on the left of a 2560×1440 px monitor at 1×, both in
+    /// the physical px the OS reports.
+    fn laptop(logical_space: bool) -> Rect {
+        area_in_units((0, 50), (2880, 1750), 2.0, logical_space)
+    }
+
+    fn external(logical_space: bool) -> Rect {
+        let x = if logical_space { 1440 } else { 2880 };
+        area_in_units((x, 0), (2560, 1440), 1.0, logical_space)
+    }
+
+    #[test]
+    fn work_areas_convert_per_monitor_scale() {
+        assert_eq!(laptop(true), (0.0, 25.0, 1440.0, 875.0));
+        assert_eq!(laptop(false), (0.0, 50.0, 2880.0, 1750.0));
+        assert_eq!(external(true), (1440.0, 0.0, 2560.0, 1440.0));
+        assert_eq!(area_in_units((10, 10), (100, 100), 0.0, true).2, 100.0);
+    }
+
+    #[test]
+    fn windows_on_a_connected_monitor_stay_put() {
+        for logical in [true, false] {
+            let areas = [laptop(logical), external(logical)];
+            let on_external = (external(logical).0 + 100.0, 200.0, 800.0, 600.0);
+            assert_eq!(
+                place(on_external, &areas, areas[0], MIN_VISIBLE),
+                (on_external.0, 200.0)
+            );
+        }
+    }
+
+    #[test]
+    fn a_window_mostly_off_the_edge_stays_while_it_can_be_grabbed() {
+        let areas = [laptop(true)];
+        let peeking = (1440.0 - MIN_VISIBLE, 100.0, 800.0, 600.0);
+        assert_eq!(
+            place(peeking, &areas, areas[0], MIN_VISIBLE),
+            (1376.0, 100.0)
+        );
+        let hidden = (1440.0 - MIN_VISIBLE + 1.0, 100.0, 800.0, 600.0);
+        assert_eq!(place(hidden, &areas, areas[0], MIN_VISIBLE), (640.0, 100.0));
+    }
+
+    #[test]
+    fn windows_from_a_disconnected_monitor_come_back() {
+        // Saved on the external monitor, which is gone: only the laptop is left.
+        let areas = [laptop(true)];
+        let saved = (2000.0, 300.0, 800.0, 600.0);
+        assert_eq!(place(saved, &areas, areas[0], MIN_VISIBLE), (640.0, 300.0));
+        // Taller than the work area: pinned to its top.
+        let tall = (2000.0, 300.0, 800.0, 2000.0);
+        assert_eq!(place(tall, &areas, areas[0], MIN_VISIBLE), (640.0, 25.0));
+    }
+
+    #[test]
+    fn monitors_left_of_and_above_the_primary_have_negative_origins() {
+        let primary = area_in_units((0, 0), (1920, 1080), 1.0, false);
+        let left = area_in_units((-1920, -200), (1920, 1080), 1.0, false);
+        let areas = [primary, left];
+        let on_left = (-1000.0, -100.0, 800.0, 600.0);
+        assert_eq!(
+            place(on_left, &areas, primary, MIN_VISIBLE),
+            (-1000.0, -100.0)
+        );
+        // Above the left monitor's top edge: back inside it.
+        let above = (-1000.0, -900.0, 800.0, 600.0);
+        assert_eq!(place(above, &[left], left, MIN_VISIBLE), (-1000.0, -200.0));
+    }
+
+    #[test]
+    fn small_windows_need_only_be_fully_visible() {
+        let areas = [laptop(true)];
+        let tiny = (1400.0, 100.0, 40.0, 40.0);
+        assert_eq!(place(tiny, &areas, areas[0], MIN_VISIBLE), (1400.0, 100.0));
+        let clipped = (1401.0, 100.0, 40.0, 40.0);
+        assert_eq!(
+            place(clipped, &areas, areas[0], MIN_VISIBLE),
+            (1400.0, 100.0)
+        );
+    }
+}
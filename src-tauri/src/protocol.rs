@@ -0,0 +1,260 @@
+//! `float://` URI scheme that streams image files to the webview.
+//!
+//! The webview requests `float://localhost/<percent-encoded path>` (or
+//! `http://float.localhost/...` on Windows, see `convertFileSrc(path, "float")`)
+//! and gets the raw file bytes back with the right MIME type, instead of a base64
+//! `data:` URL shipped over IPC.
+//!
+//! Tauri hands each response body over in one buffer, so reads are bounded instead:
+//! a response never holds more than the file's length at open time, and an open-ended
+//! range (`bytes=N-`, as media loaders send) gets at most [`MAX_CHUNK`] bytes and asks
+//! for the rest through `Content-Range`.
+
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use percent_encoding::percent_decode_str;
+use tauri::http::{header, Request, Response, StatusCode};
+
+use float_core::selection::is_image_path;
+
+use crate::{decode, Error};
+
+pub const SCHEME: &str = "float";
+
+/// Most bytes answered to an open-ended range request.
+const MAX_CHUNK: u64 = 4 * 1024 * 1024;
+
+pub fn mime_for_path(path: &Path) -> &'static str {
+    match path
+        .extension()
+        .and_then(|s| s.to_str())
+        .map(|s| s.to_ascii_lowercase())
+        .as_deref()
+    {
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("bmp") => "image/bmp",
+        Some("tif") | Some("tiff") => "image/tiff",
+        Some("heic") => "image/heic",
+        _ => "application/octet-stream",
+    }
+}
+
+/// The file path in a request URI's path component, e.g. `/%2FUsers%2Fme%2Fa.png`.
+fn requested_path(uri_path: &str) -> Option<PathBuf> {
+    let raw = uri_path.trim_start_matches('/');
+    let decoded = percent_decode_str(raw).decode_utf8().ok()?;
+    if decoded.is_empty() {
+        return None;
+    }
+    Some(PathBuf::from(decoded.as_ref()))
+}
+
+/// Parses a single `bytes=` range against a file of `len` bytes into an inclusive
+/// `(start, end)` pair. Only the first range of a multi-range request is honored, and
+/// an open-ended one is cut to [`MAX_CHUNK`] bytes.
+fn parse_range(value: &str, len: u64) -> Option<(u64, u64)> {
+    let spec = value.trim().strip_prefix("bytes=")?;
+    let first = spec.split(',').next()?.trim();
+    let (start, end) = first.split_once('-')?;
+    if len == 0 {
+        return None;
+    }
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let suffix: u64 = suffix.parse().ok()?;
+            if suffix == 0 {
+                return None;
+            }
+            (len.saturating_sub(suffix), len - 1)
+        }
+        (start, "") => {
+            let start: u64 = start.parse().ok()?;
+            (start, start.saturating_add(MAX_CHUNK - 1).min(len - 1))
+        }
+        (start, end) => (start.parse().ok()?, end.parse::<u64>().ok()?.min(len - 1)),
+    };
+    if start > end || start >= len {
+        return None;
+    }
+    Some((start, end))
+}
+
+fn status(code: StatusCode) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(code)
+        .body(Vec::new())
+        .unwrap_or_default()
+}
+
+/// Reads bytes `start..=end`, and no more even if the file grew since it was measured.
+fn read_range(path: &Path, start: u64, end: u64) -> std::io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(start))?;
+    let mut buf = Vec::with_capacity((end - start + 1) as usize);
+    file.take(end - start + 1).read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+/// Serves formats the webview can't render as PNG. Range requests are answered with
+/// the full body since the transcoded size isn't known up front.
+fn transcoded(path: &Path) -> Response<Vec<u8>> {
+    match decode::transcode_to_png(path) {
+        Ok(bytes) => Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "image/png")
+            .header(header::CONTENT_LENGTH, bytes.len())
+            .header(header::CACHE_CONTROL, "no-cache")
+            .body(bytes)
+            .unwrap_or_else(|_| status(StatusCode::INTERNAL_SERVER_ERROR)),
+        Err(Error::CodecUnavailable(_)) => status(StatusCode::UNSUPPORTED_MEDIA_TYPE),
+        Err(_) => status(StatusCode::UNPROCESSABLE_ENTITY),
+    }
+}
+
+pub fn handle(request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let path = match requested_path(request.uri().path()) {
+        Some(p) => p,
+        None => return status(StatusCode::BAD_REQUEST),
+    };
+    // Same allow-list as the file picker: never serve anything that isn't an image.
+    if !is_image_path(&path.to_string_lossy()) {
+        return status(StatusCode::FORBIDDEN);
+    }
+    let len = match path.metadata() {
+        Ok(meta) if meta.is_file() => meta.len(),
+        _ => return status(StatusCode::NOT_FOUND),
+    };
+    if !decode::is_web_native(&path) {
+        return transcoded(&path);
+    }
+    let mime = mime_for_path(&path);
+
+    let range = request
+        .headers()
+        .get(header::RANGE)
+        .and_then(|v| v.to_str().ok());
+    let builder = Response::builder()
+        .header(header::CONTENT_TYPE, mime)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::CACHE_CONTROL, "no-cache");
+
+    let response = match range {
+        Some(value) => match parse_range(value, len) {
+            Some((start, end)) => match read_range(&path, start, end) {
+                Ok(bytes) => builder
+                    .status(StatusCode::PARTIAL_CONTENT)
+                    .header(header::CONTENT_RANGE, format!("bytes {start}-{end}/{len}"))
+                    .header(header::CONTENT_LENGTH, bytes.len())
+                    .body(bytes),
+                Err(_) => return status(StatusCode::INTERNAL_SERVER_ERROR),
+            },
+            None => builder
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(header::CONTENT_RANGE, format!("bytes */{len}"))
+                .body(Vec::new()),
+        },
+        None if len == 0 => builder
+            .status(StatusCode::OK)
+            .header(header::CONTENT_LENGTH, 0)
+            .body(Vec::new()),
+        None => match read_range(&path, 0, len - 1) {
+            Ok(bytes) => builder
+                .status(StatusCode::OK)
+                .header(header::CONTENT_LENGTH, bytes.len())
+                .body(bytes),
+            Err(_) => return status(StatusCode::INTERNAL_SERVER_ERROR),
+        },
+    };
+    response.unwrap_or_else(|_| status(StatusCode::INTERNAL_SERVER_ERROR))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closed_ranges_are_clamped_to_the_file() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Some((0, 99)));
+        assert_eq!(parse_range(" bytes=10-10 ", 1000), Some((10, 10)));
+        assert_eq!(parse_range("bytes=900-5000", 1000), Some((900, 999)));
+    }
+
+    #[test]
+    fn suffix_ranges_count_from_the_end() {
+        assert_eq!(parse_range("bytes=-100", 1000), Some((900, 999)));
+        assert_eq!(parse_range("bytes=-5000", 1000), Some((0, 999)));
+        assert_eq!(parse_range("bytes=-0", 1000), None);
+    }
+
+    #[test]
+    fn open_ranges_stop_after_one_chunk() {
+        assert_eq!(parse_range("bytes=100-", 1000), Some((100, 999)));
+        let len = 3 * MAX_CHUNK;
+        assert_eq!(parse_range("bytes=0-", len), Some((0, MAX_CHUNK - 1)));
+        assert_eq!(parse_range("bytes=5-", len), Some((5, MAX_CHUNK + 4)));
+        assert_eq!(parse_range(&format!("bytes={}-", u64::MAX), len), None);
+    }
+
+    #[test]
+    fn unsatisfiable_ranges_are_rejected() {
+        assert_eq!(parse_range("bytes=1000-", 1000), None);
+        assert_eq!(parse_range("bytes=2000-3000", 1000), None);
+        assert_eq!(parse_range("bytes=50-10", 1000), None);
+        assert_eq!(parse_range("bytes=0-", 0), None);
+        assert_eq!(parse_range("bytes=-10", 0), None);
+    }
+
+    #[test]
+    fn only_the_first_of_several_ranges_is_served() {
+        assert_eq!(parse_range("bytes=0-9, 20-29", 1000), Some((0, 9)));
+        assert_eq!(parse_range("bytes=x-9, 20-29", 1000), None);
+    }
+
+    #[test]
+    fn malformed_ranges_are_rejected() {
+        assert_eq!(parse_range("0-99", 1000), None);
+        assert_eq!(parse_range("items=0-99", 1000), None);
+        assert_eq!(parse_range("bytes=", 1000), None);
+        assert_eq!(parse_range("bytes=abc-def", 1000), None);
+        assert_eq!(parse_range("bytes=-1-2", 1000), None);
+    }
+
+    #[test]
+    fn paths_are_percent_decoded() {
+        assert_eq!(
+            requested_path("/%2FUsers%2Fme%2Fmy%20shot.png"),
+            Some(PathBuf::from("/Users/me/my shot.png"))
+        );
+        assert_eq!(
+            requested_path("/C%3A%5Crefs%5Ca.png"),
+            Some(PathBuf::from("C:\\refs\\a.png"))
+        );
+        assert_eq!(
+            requested_path("/%E6%97%A5%E6%9C%AC.jpg"),
+            Some(PathBuf::from("日本.jpg"))
+        );
+    }
+
+    #[test]
+    fn empty_and_badly_encoded_paths_are_rejected() {
+        assert_eq!(requested_path("/"), None);
+        assert_eq!(requested_path(""), None);
+        // %FF is not UTF-8 on its own.
+        assert_eq!(requested_path("/%FFa.png"), None);
+    }
+
+    #[test]
+    fn mime_types_ignore_extension_case() {
+        assert_eq!(mime_for_path(Path::new("/a.PNG")), "image/png");
+        assert_eq!(mime_for_path(Path::new("/a.Jpeg")), "image/jpeg");
+        assert_eq!(mime_for_path(Path::new("/a.tif")), "image/tiff");
+        assert_eq!(mime_for_path(Path::new("/a")), "application/octet-stream");
+    }
+}
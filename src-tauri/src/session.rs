@@ -0,0 +1,127 @@
+//! Per-window session: what each window shows and where it sits, saved with the
+//! settings on every `save_state` and recreated on launch.
+
+use std::{collections::BTreeMap, sync::atomic::Ordering};
+
+use float_core::{
+    selection::{natural_cmp, SelectionState},
+    settings::WindowSession,
+};
+use tauri::{AppHandle, Manager, WebviewWindow};
+
+use crate::{error, placement, AppState};
+
+fn capture(app: &AppHandle, window: &WebviewWindow) -> WindowSession {
+    let label = window.label();
+    let mut session = WindowSession::default();
+    if let Some(state) = app.try_state::<AppState>() {
+        if let Some(sel) = state.selections.lock().get(label) {
+            session.files = sel.files.clone();
+            session.active = sel.active;
+        }
+        session.opacity = state.opacities.lock().get(label).copied();
+        session.aspect_lock = state.aspect_locks.lock().get(label).copied();
+        session.click_through = state.click_through.lock().contains(label);
+        session.view = state.views.lock().get(label).copied();
+    }
+    if let Some((w, h)) = crate::logical_outer_size(window) {
+        session.width = Some(w);
+        session.height = Some(h);
+    }
+    if let Some((x, y, monitor)) = placement::capture(window) {
+        session.x = Some(x);
+        session.y = Some(y);
+        session.monitor = monitor;
+    }
+    session
+}
+
+/// Snapshot of every open window, keyed by label.
+pub fn collect(app: &AppHandle) -> BTreeMap<String, WindowSession> {
+    app.webview_windows()
+        .iter()
+        .map(|(label, window)| (label.clone(), capture(app, window)))
+        .collect()
+}
+
+fn apply(app: &AppHandle, window: &WebviewWindow, session: &WindowSession) {
+    if let (Some(width), Some(height)) = (session.width, session.height) {
+        let size = tauri::Size::Logical(tauri::LogicalSize { width, height });
+        if let Err(err) = window.set_size(size) {
+            error::report(window, None, &err.into());
+        }
+    }
+    if let (Some(x), Some(y)) = (session.x, session.y) {
+        placement::restore(window, x, y, session.monitor.as_ref());
+    }
+    if let Some(percent) = session.opacity {
+        crate::set_window_opacity(app, window, percent);
+    }
+    if session.click_through {
+        crate::set_click_through(app, window, true);
+    }
+    if let Some(state) = app.try_state::<AppState>() {
+        if let Some(view) = session.view {
+            state.views.lock().insert(window.label().to_string(), view);
+        }
+        if let Some(lock) = session.aspect_lock {
+            state
+                .aspect_locks
+                .lock()
+                .insert(window.label().to_string(), lock);
+        }
+        if session.files.is_empty() {
+            return;
+        }
+        let selection = SelectionState {
+            files: session.files.clone(),
+            active: session.active.min(session.files.len() - 1),
+        };
+        state
+            .selections
+            .lock()
+            .insert(window.label().to_string(), selection.clone());
+        let _ = crate::apply_active_file(app, window, &selection, crate::ApplyReason::Restore);
+    }
+}
+
+/// Recreates the saved windows: `main` reuses its own session (or the first one) and
+/// every other session gets a new window. Returns `false` when nothing was saved.
+pub fn restore(
+    app: &AppHandle,
+    main: &WebviewWindow,
+    sessions: &BTreeMap<String, WindowSession>,
+) -> bool {
+    let mut ordered: Vec<(&String, &WindowSession)> = sessions.iter().collect();
+    ordered.sort_by(|(a, _), (b, _)| {
+        (a.as_str() != "main")
+            .cmp(&(b.as_str() != "main"))
+            .then_with(|| natural_cmp(a, b))
+    });
+    let mut ordered = ordered.into_iter().map(|(_, session)| session);
+    let first = match ordered.next() {
+        Some(first) => first,
+        None => return false,
+    };
+
+    // Hold off saving until every window exists, otherwise the first save would record
+    // a session with only the windows restored so far.
+    let state = app.try_state::<AppState>();
+    if let Some(state) = &state {
+        state.persistence_paused.store(true, Ordering::SeqCst);
+    }
+    apply(app, main, first);
+    for session in ordered {
+        if let Ok(window) = crate::build_window(app) {
+            apply(app, &window, session);
+        }
+    }
+    if let Some(state) = &state {
+        state.persistence_paused.store(false, Ordering::SeqCst);
+        let st = state.settings.lock().clone();
+        if let Err(err) = crate::save_state(app, main, st) {
+            error::report(main, None, &err);
+        }
+    }
+    true
+}
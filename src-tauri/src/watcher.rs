@@ -0,0 +1,130 @@
+//! Per-window watcher on the active file so re-exported images reload in place.
+
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use notify::{
+    event::{AccessKind, AccessMode},
+    EventKind, RecommendedWatcher, RecursiveMode, Watcher,
+};
+use tauri::{async_runtime, AppHandle, Manager};
+use tokio::time::sleep;
+
+use crate::{reload_active_file, AppState};
+
+pub struct ActiveWatch {
+    path: String,
+    _watcher: RecommendedWatcher,
+}
+
+/// A debounced reload waiting to run. `id` tells a finished task whether the entry is
+/// still its own or was replaced by a later event.
+pub struct PendingReload {
+    id: u64,
+    handle: async_runtime::JoinHandle<()>,
+}
+
+impl PendingReload {
+    pub fn abort(&self) {
+        self.handle.abort();
+    }
+}
+
+static NEXT_RELOAD_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Writes, creations, removals and renames. Plain reads are ignored, otherwise serving
+/// the file to the webview would trigger another reload.
+fn is_content_change(kind: &EventKind) -> bool {
+    matches!(
+        kind,
+        EventKind::Create(_)
+            | EventKind::Modify(_)
+            | EventKind::Remove(_)
+            | EventKind::Access(AccessKind::Close(AccessMode::Write))
+    )
+}
+
+/// Starts watching `path` for the window `label`, replacing any watch on a previous
+/// active file. Watching the same path again is a no-op.
+pub fn watch(app: &AppHandle, label: &str, path: &str) {
+    let state = match app.try_state::<AppState>() {
+        Some(state) => state,
+        None => return,
+    };
+    let mut watchers = state.watchers.lock();
+    if watchers.get(label).is_some_and(|w| w.path == path) {
+        return;
+    }
+    watchers.remove(label);
+
+    let target = PathBuf::from(path);
+    let (dir, name) = match (target.parent(), target.file_name()) {
+        (Some(dir), Some(name)) => (dir.to_path_buf(), name.to_os_string()),
+        _ => return,
+    };
+    let app_for_events = app.clone();
+    let label_for_events = label.to_string();
+    let watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if let Ok(event) = res {
+            if is_content_change(&event.kind)
+                && event
+                    .paths
+                    .iter()
+                    .any(|p| p.file_name() == Some(name.as_os_str()))
+            {
+                schedule_reload(app_for_events.clone(), label_for_events.clone());
+            }
+        }
+    });
+    if let Ok(mut watcher) = watcher {
+        // Watch the directory rather than the file: exporters often write a temp file
+        // and rename it over the original, which silently drops a per-file watch.
+        if watcher.watch(&dir, RecursiveMode::NonRecursive).is_ok() {
+            watchers.insert(
+                label.to_string(),
+                ActiveWatch {
+                    path: path.to_string(),
+                    _watcher: watcher,
+                },
+            );
+        }
+    }
+}
+
+pub fn unwatch(app: &AppHandle, label: &str) {
+    if let Some(state) = app.try_state::<AppState>() {
+        state.watchers.lock().remove(label);
+        if let Some(pending) = state.pending_reload.lock().remove(label) {
+            pending.abort();
+        }
+    }
+}
+
+/// Debounces bursts of filesystem events (a single save can emit several) into one reload.
+fn schedule_reload(app: AppHandle, label: String) {
+    if let Some(state) = app.try_state::<AppState>() {
+        let mut pending = state.pending_reload.lock();
+        if let Some(previous) = pending.remove(&label) {
+            previous.abort();
+        }
+        let id = NEXT_RELOAD_ID.fetch_add(1, Ordering::Relaxed);
+        let app_for_task = app.clone();
+        let label_for_task = label.clone();
+        let handle = async_runtime::spawn(async move {
+            sleep(Duration::from_millis(250)).await;
+            if let Some(win) = app_for_task.get_webview_window(&label_for_task) {
+                reload_active_file(&app_for_task, &win);
+            }
+            if let Some(state) = app_for_task.try_state::<AppState>() {
+                let mut pending = state.pending_reload.lock();
+                if pending.get(&label_for_task).is_some_and(|p| p.id == id) {
+                    pending.remove(&label_for_task);
+                }
+            }
+        });
+        pending.insert(label, PendingReload { id, handle });
+    }
+}
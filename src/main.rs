@@ -1,12 +1,14 @@
 #![allow(unexpected_cfgs)] // Allow objc macro cfg probes under clippy
 
 use std::fs;
-use std::io::{Read, Write};
 use std::path::PathBuf;
 use std::process::Command;
 use std::thread;
 
 use directories::{BaseDirs, ProjectDirs};
+use float_core::aspect;
+use float_core::settings::{PersistedState, WindowSizeUnits};
+use float_core::store::{FileStore, SettingsStore};
 use winit::dpi::LogicalSize;
 use winit::event::{
     ElementState, Event, KeyboardInput, ModifiersState, VirtualKeyCode, WindowEvent,
@@ -44,15 +46,6 @@ static SETTINGS: Lazy<Mutex<Settings>> = Lazy::new(|| {
 static IMAGE_ASPECT: Lazy<Mutex<Option<f64>>> = Lazy::new(|| Mutex::new(None));
 static RESIZE_GUARD: Lazy<Mutex<bool>> = Lazy::new(|| Mutex::new(false));
 
-#[derive(serde::Serialize, serde::Deserialize)]
-struct PersistedState {
-    fit_window: bool,
-    aspect_lock: bool,
-    last_file: Option<String>,
-    window_w: Option<f64>,
-    window_h: Option<f64>,
-}
-
 const APP_NAME: &str = "Float";
 const APP_ORG: &str = "havesomecode";
 const LEGACY_APP_NAME: &str = "Always On Top";
@@ -106,16 +99,18 @@ fn config_path() -> Option<std::path::PathBuf> {
 }
 
 fn load_persisted() -> Option<PersistedState> {
-    let path = config_path()?;
-    let mut s = String::new();
-    if let Ok(mut f) = fs::File::open(path) {
-        if f.read_to_string(&mut s).is_ok() {
-            if let Ok(st) = serde_json::from_str::<PersistedState>(&s) {
-                return Some(st);
+    let store = FileStore::new(config_path()?);
+    match store.load() {
+        Ok(st) => st,
+        Err(err) => {
+            // Keep an unreadable file for the user instead of overwriting it.
+            if !matches!(err, float_core::Error::Io { .. }) {
+                store.back_up();
             }
+            eprintln!("settings unreadable ({err})");
+            None
         }
     }
-    None
 }
 
 fn save_persisted(selected: Option<&PathBuf>, window: &winit::window::Window) {
@@ -141,16 +136,16 @@ fn save_persisted(selected: Option<&PathBuf>, window: &winit::window::Window) {
     let window_w = Some(size.width as f64 / sf);
     let window_h = Some(size.height as f64 / sf);
     let state = PersistedState {
-        fit_window,
+        fit_on_open: fit_window,
         aspect_lock,
         last_file,
         window_w,
         window_h,
+        window_size_units: Some(WindowSizeUnits::Logical),
+        ..Default::default()
     };
-    if let Ok(data) = serde_json::to_string_pretty(&state) {
-        if let Ok(mut f) = fs::File::create(path) {
-            let _ = f.write_all(data.as_bytes());
-        }
+    if let Err(err) = FileStore::new(path).save(&state) {
+        eprintln!("failed to save settings: {err}");
     }
 }
 
@@ -521,7 +516,7 @@ fn main() {
     let mut selected: Option<PathBuf> = None;
     if let Some(st) = load_persisted() {
         if let Ok(mut s) = SETTINGS.lock() {
-            s.fit_window = st.fit_window;
+            s.fit_window = st.fit_on_open;
             s.aspect_lock = st.aspect_lock;
         }
         // Restore window size first (logical points)
@@ -554,8 +549,7 @@ fn main() {
     #[cfg(target_os = "macos")]
     if let Some(ref p) = selected {
         if let Some((w, h)) = macos_image::set_image(&window, p) {
-            let aspect = if h > 0.0 { w / h } else { 0.0 };
-            if aspect > 0.0 {
+            if let Some(aspect) = aspect::ratio((w, h)) {
                 if let Ok(mut a) = IMAGE_ASPECT.lock() {
                     *a = Some(aspect);
                 }
@@ -602,8 +596,7 @@ fn main() {
                                 #[cfg(target_os = "macos")]
                                 if let Some(ref p) = selected {
                                     if let Some((w, h)) = macos_image::set_image(&window, p) {
-                                        let aspect = if h > 0.0 { w / h } else { 0.0 };
-                                        if aspect > 0.0 {
+                                        if let Some(aspect) = aspect::ratio((w, h)) {
                                             if let Ok(mut a) = IMAGE_ASPECT.lock() {
                                                 *a = Some(aspect);
                                             }
@@ -690,8 +683,7 @@ fn main() {
                 #[cfg(target_os = "macos")]
                 if let Some(ref p) = selected {
                     if let Some((w, h)) = macos_image::set_image(&window, p) {
-                        let aspect = if h > 0.0 { w / h } else { 0.0 };
-                        if aspect > 0.0 {
+                        if let Some(aspect) = aspect::ratio((w, h)) {
                             if let Ok(mut a) = IMAGE_ASPECT.lock() {
                                 *a = Some(aspect);
                             }